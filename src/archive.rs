@@ -0,0 +1,53 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+use zip::{ZipArchive, result::ZipError};
+
+/// An entry of a zip archive.
+pub struct ArchiveEntry {
+    /// Where the entry ends up when the archive is extracted, see [`extracted_entries`].
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// The uncompressed size in bytes, 0 for directories.
+    pub size: u64,
+}
+
+pub fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+/// Lists the entries of the zip archive at `path`, as if it was extracted into a folder of the
+/// same name next to it, which is what Windows suggests when extracting.
+///
+/// Only the index at the end of the archive is read, so this stays fast for big archives.
+/// Entries which would end up outside of that folder are skipped, extracting refuses them too.
+pub fn extracted_entries(path: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let file = BufReader::new(File::open(path)?);
+    let mut archive = ZipArchive::new(file).map_err(into_io_error)?;
+    let target = path.with_extension("");
+
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(into_io_error)?;
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        entries.push(ArchiveEntry {
+            path: target.join(name),
+            is_dir: entry.is_dir(),
+            size: if entry.is_dir() { 0 } else { entry.size() },
+        });
+    }
+    Ok(entries)
+}
+
+fn into_io_error(err: ZipError) -> io::Error {
+    match err {
+        ZipError::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use tokio::fs;
+
+use crate::{export::JsonReport, scanner::OverLimit};
+
+/// The paths over the limit of an earlier scan, loaded from one of its exports.
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    /// The export the paths were loaded from.
+    pub file: PathBuf,
+    /// The length of each path, by the full path.
+    paths: HashMap<String, u64>,
+}
+
+/// How the current results differ from a [`Baseline`].
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+    /// Paths over the limit now which weren't before.
+    pub new: u64,
+    /// Paths over the limit in both.
+    pub unchanged: u64,
+    /// Paths which were over the limit before but aren't anymore, with their old length.
+    pub fixed: Vec<(String, u64)>,
+}
+
+impl Baseline {
+    /// Loads a JSON export or a CSV export with any delimiter.
+    ///
+    /// Exports with relative paths can only be loaded from JSON, which records the folder each
+    /// path is relative to.
+    pub async fn load(file: PathBuf) -> Result<Self, String> {
+        let content = fs::read_to_string(&file)
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+
+        let is_json = file
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let paths = if is_json {
+            parse_json(&content)?
+        } else {
+            parse_csv(&content)?
+        };
+
+        Ok(Self { file, paths })
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.contains_key(path)
+    }
+
+    /// Compares the baseline with `current`, the results of a scan of `roots`.
+    ///
+    /// Paths of the baseline outside of `roots` weren't scanned again, so they don't count as fixed.
+    pub fn compare<'a>(
+        &self,
+        current: impl IntoIterator<Item = &'a OverLimit>,
+        roots: &[PathBuf],
+    ) -> Comparison {
+        let mut comparison = Comparison::default();
+        let mut current_paths = HashSet::new();
+        for path in current {
+            if self.contains(&path.path) {
+                comparison.unchanged += 1;
+            } else {
+                comparison.new += 1;
+            }
+            current_paths.insert(path.path.as_str());
+        }
+
+        comparison.fixed = self
+            .paths
+            .iter()
+            .filter(|(path, _)| {
+                !current_paths.contains(path.as_str())
+                    && roots.iter().any(|root| Path::new(path).starts_with(root))
+            })
+            .map(|(path, length)| (path.clone(), *length))
+            .collect();
+        comparison.fixed.sort();
+        comparison
+    }
+}
+
+fn parse_json(content: &str) -> Result<HashMap<String, u64>, String> {
+    let report: JsonReport = serde_json::from_str(content)
+        .map_err(|e| format!("Failed to parse the JSON report: {}", e))?;
+    Ok(report
+        .paths
+        .into_iter()
+        .map(|path| {
+            let full = if report.relative_paths {
+                Path::new(&path.root)
+                    .join(&path.path)
+                    .to_string_lossy()
+                    .to_string()
+            } else {
+                path.path
+            };
+            (full, path.length)
+        })
+        .collect())
+}
+
+/// Reads the length and path columns of a CSV export, guessing the delimiter from the header.
+fn parse_csv(content: &str) -> Result<HashMap<String, u64>, String> {
+    let mut lines = content.lines();
+    let header = lines.next().ok_or("The CSV file is empty")?;
+    if header.contains(", relative)") {
+        return Err(
+            "The CSV file has relative paths, export the previous results as JSON to compare them"
+                .to_string(),
+        );
+    }
+    let delimiter = [';', '\t', ',']
+        .into_iter()
+        .find(|delimiter| header.contains(&format!("{}Path{}", delimiter, delimiter)))
+        .ok_or("The CSV file isn't an export of this app")?;
+
+    let mut paths = HashMap::new();
+    for line in lines.filter(|line| !line.is_empty()) {
+        let (length, rest) = line
+            .split_once(delimiter)
+            .ok_or_else(|| format!("Invalid CSV row: {}", line))?;
+        let length = length
+            .parse()
+            .map_err(|_| format!("Invalid length in CSV row: {}", line))?;
+        paths.insert(read_field(rest, delimiter), length);
+    }
+    Ok(paths)
+}
+
+/// Reads the first field of `row`, undoing the quoting of the export.
+fn read_field(row: &str, delimiter: char) -> String {
+    let Some(quoted) = row.strip_prefix('"') else {
+        return row.split(delimiter).next().unwrap_or_default().to_string();
+    };
+
+    let mut field = String::new();
+    let mut chars = quoted.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('"', Some('"')) | ('\\', Some('\\')) => {
+                field.push(c);
+                chars.next();
+            }
+            ('"', _) => break,
+            _ => field.push(c),
+        }
+    }
+    field
+}
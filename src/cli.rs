@@ -0,0 +1,325 @@
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Instant, SystemTime},
+};
+
+use clap::Parser;
+use serde::Serialize;
+
+use crate::{
+    date,
+    export::{self, CsvOptions, Delimiter, JsonPath, JsonReport, Separators},
+    measure::LengthMetric,
+    project,
+    scanner::{self, ScanConfig, ScanOptions, ScanReport},
+    segments::SegmentTally,
+    serve,
+    top::TopPaths,
+};
+
+/// Scans a folder for paths over the length limit without opening the window.
+///
+/// Exits with 1 if any path is over the limit and with 2 if the scan couldn't be completed.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Args {
+    /// The folder to scan.
+    #[arg(long)]
+    path: PathBuf,
+    /// Paths longer than this are reported.
+    ///
+    /// Defaults to the limit in the `.pathlen-limit` file of the folder, or 240 without one.
+    #[arg(long)]
+    limit: Option<usize>,
+    /// The unit path lengths are counted in.
+    #[arg(long, value_enum, default_value_t = LengthMetric::default())]
+    metric: LengthMetric,
+    /// Composes paths to Unicode NFC before measuring them, so decomposed names count the same.
+    #[arg(long)]
+    normalize_unicode: bool,
+    /// Also reports the folder itself if its path is over the limit.
+    #[arg(long)]
+    include_root: bool,
+    /// Skips hidden files and folders, and everything whose name starts with a dot.
+    #[arg(long)]
+    skip_hidden: bool,
+    /// Also checks the entries of zip archives, as if they were extracted next to the archive.
+    #[cfg(feature = "zip")]
+    #[arg(long)]
+    scan_archives: bool,
+    /// Only reports files with this extension, can be given several times.
+    #[arg(long = "extension", value_name = "EXTENSION")]
+    extensions: Vec<String>,
+    /// Only reports paths modified on or after this day, as YYYY-MM-DD.
+    #[arg(long, value_name = "DATE", value_parser = date::parse_date)]
+    modified_since: Option<SystemTime>,
+    /// Only reports paths modified on or before this day, as YYYY-MM-DD.
+    #[arg(long, value_name = "DATE", value_parser = date::parse_date)]
+    modified_until: Option<SystemTime>,
+    /// How often reading a folder is tried again after an error which may go away.
+    #[arg(long, default_value_t = ScanOptions::DEFAULT_RETRIES)]
+    retries: u32,
+    /// Writes the paths over the limit to this CSV file.
+    #[arg(long)]
+    export: Option<PathBuf>,
+    /// The column separator of the exported file.
+    #[arg(long, value_enum, default_value_t = Delimiter::default())]
+    delimiter: Delimiter,
+    /// How the separators of the exported and printed paths are written.
+    #[arg(long, value_enum, default_value_t = Separators::default())]
+    separators: Separators,
+    /// Prints a JSON summary to stdout instead of the text summary, which goes to stderr then.
+    #[arg(long)]
+    json_summary: bool,
+    /// Serves the results as a JSON report on this port of localhost until Ctrl+C is pressed.
+    #[arg(long, value_name = "PORT")]
+    serve: Option<u16>,
+}
+
+/// What `--json-summary` prints.
+#[derive(Debug, Serialize)]
+struct JsonSummary {
+    path: String,
+    limit: usize,
+    metric: String,
+    scanned: u64,
+    files: u64,
+    directories: u64,
+    over_limit: usize,
+    errors: u64,
+    duration_seconds: f64,
+    /// The longest paths over the limit, the longest first.
+    top: Vec<JsonPath>,
+}
+
+pub fn run(args: Args) -> ExitCode {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("Failed to start the async runtime: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    runtime.block_on(scan(args))
+}
+
+/// The limit used without `--limit` or a `.pathlen-limit` file.
+const DEFAULT_LIMIT: usize = 240;
+
+async fn scan(args: Args) -> ExitCode {
+    let (limit, limit_source) = match args.limit {
+        Some(limit) => (limit, "--limit"),
+        None => match project::read_limit(&args.path).await {
+            Some(limit) => (limit, project::LIMIT_FILE),
+            None => (DEFAULT_LIMIT, "the default"),
+        },
+    };
+
+    let extensions: Vec<String> = args
+        .extensions
+        .iter()
+        .map(|extension| scanner::normalize_extension(extension))
+        .collect();
+    let options = ScanOptions {
+        normalize_unicode: args.normalize_unicode,
+        include_roots: args.include_root,
+        skip_hidden: args.skip_hidden,
+        extensions: extensions.clone(),
+        modified_since: args.modified_since,
+        modified_until: args.modified_until,
+        retries: args.retries,
+        #[cfg(feature = "zip")]
+        scan_archives: args.scan_archives,
+        ..ScanOptions::new(limit, args.metric)
+    };
+
+    let started = Instant::now();
+    let report = match scanner::run_scan(ScanConfig {
+        roots: vec![args.path.clone()],
+        options,
+    })
+    .await
+    {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::from(2);
+        }
+    };
+    for error in &report.errors {
+        eprintln!("{}", error);
+    }
+    let errors = report.errors.len() as u64;
+    let ScanReport {
+        scanned,
+        files,
+        directories,
+        deepest,
+        over_limit,
+        ..
+    } = report;
+
+    if let Some(export) = &args.export {
+        let options = CsvOptions {
+            limit,
+            metric: args.metric,
+            suggested_names: true,
+            name_lengths: false,
+            roots: false,
+            error_limit: None,
+            suggested_paths: false,
+            relative: false,
+            delimiter: args.delimiter,
+            separators: args.separators,
+        };
+        if let Err(err) = export::write_csv(export, &over_limit, &options).await {
+            eprintln!("{}", err);
+            return ExitCode::from(2);
+        }
+    }
+
+    let duration = started.elapsed();
+
+    // With a JSON summary, stdout only gets the JSON so it can be parsed
+    let log = |line: String| {
+        if args.json_summary {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    };
+    log(format!(
+        "Using the limit of {} from {}",
+        limit, limit_source
+    ));
+    log(format!(
+        "Scanned {} paths in {}: {} over the limit of {} ({}), {} errors",
+        scanned,
+        args.path.display(),
+        over_limit.len(),
+        limit,
+        args.metric,
+        errors
+    ));
+    if let Some(export) = &args.export {
+        log(format!(
+            "Exported the paths over the limit to {}",
+            export.display()
+        ));
+    }
+
+    if args.json_summary {
+        let mut top = TopPaths::default();
+        top.rebuild(&over_limit, TopPaths::DEFAULT_CAPACITY);
+        let summary = JsonSummary {
+            path: args.path.to_string_lossy().to_string(),
+            limit,
+            metric: args.metric.to_string(),
+            scanned,
+            files,
+            directories,
+            over_limit: over_limit.len(),
+            errors,
+            duration_seconds: duration.as_secs_f64(),
+            top: top
+                .sorted()
+                .into_iter()
+                .map(|path| JsonPath {
+                    path: args
+                        .separators
+                        .apply(Cow::Borrowed(path.path.as_str()))
+                        .to_string(),
+                    length: path.size,
+                    root: path.root.to_string(),
+                    severity: None,
+                    archive: path.archive.as_deref().map(str::to_string),
+                })
+                .collect(),
+        };
+        match serde_json::to_string(&summary) {
+            Ok(json) => println!("{}", json),
+            Err(err) => {
+                eprintln!("Failed to serialize JSON summary: {}", err);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    if let Some(port) = args.serve {
+        let mut segments = SegmentTally::default();
+        for path in &over_limit {
+            segments.record(path);
+        }
+        let report = JsonReport {
+            roots: vec![args.path.to_string_lossy().to_string()],
+            limit,
+            error_limit: None,
+            metric: args.metric.to_string(),
+            extensions,
+            modified_since: args.modified_since.map(date::format_date),
+            modified_until: args.modified_until.map(date::format_date),
+            relative_paths: false,
+            deepest_nesting: deepest,
+            duration_seconds: Some(duration.as_secs_f64()),
+            aborted: false,
+            timestamp: export::unix_timestamp(),
+            common_segments: segments
+                .top(SegmentTally::DEFAULT_COUNT, args.metric)
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            paths: over_limit
+                .iter()
+                .map(|path| JsonPath {
+                    path: args
+                        .separators
+                        .apply(Cow::Borrowed(path.path.as_str()))
+                        .to_string(),
+                    length: path.size,
+                    root: path.root.to_string(),
+                    severity: None,
+                    archive: path.archive.as_deref().map(str::to_string),
+                })
+                .collect(),
+        };
+        let report = match serde_json::to_string_pretty(&report) {
+            Ok(report) => report,
+            Err(err) => {
+                eprintln!("Failed to serialize JSON report: {}", err);
+                return ExitCode::from(2);
+            }
+        };
+        if let Err(err) = serve::serve(port, report).await {
+            eprintln!("{}", err);
+            return ExitCode::from(2);
+        }
+    }
+
+    if over_limit.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Attaches to the console of the parent process, so output shows up in the terminal.
+///
+/// The app is built for the windows subsystem, which doesn't get a console of its own.
+#[cfg(windows)]
+pub fn attach_console() {
+    use windows_sys::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+    // SAFETY: takes no pointers, failing just means there is no console to print to
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_console() {}
@@ -0,0 +1,167 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DAY: Duration = Duration::from_secs(86_400);
+
+/// Parses a `YYYY-MM-DD` date into the start of that day in UTC.
+pub fn parse_date(text: &str) -> Result<SystemTime, String> {
+    let invalid = || format!("Invalid date {}, expected YYYY-MM-DD", text.trim());
+
+    let mut parts = text.trim().splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let year: i64 = year.parse().map_err(|_| invalid())?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    let day: u32 = day.parse().map_err(|_| invalid())?;
+    // Modification times before the epoch don't happen in practice
+    if !(1970..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+    {
+        return Err(invalid());
+    }
+
+    // Some systems can't represent times that far out
+    u32::try_from(days_from_civil(year, month, day))
+        .ok()
+        .and_then(|days| DAY.checked_mul(days))
+        .and_then(|since| UNIX_EPOCH.checked_add(since))
+        .ok_or_else(|| format!("The date {} is out of range", text.trim()))
+}
+
+/// Formats the day `time` falls on in UTC as `YYYY-MM-DD`.
+pub fn format_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / DAY.as_secs());
+    let (year, month, day) = civil_from_days(days as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the days since the Unix epoch of a date.
+///
+/// Counts in eras of 400 years starting in March, so leap days fall at the end of a year.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::{DAY, civil_from_days, days_from_civil, format_date, parse_date};
+
+    #[test]
+    fn the_epoch_is_day_zero() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(parse_date("1970-01-01"), Ok(UNIX_EPOCH));
+        assert_eq!(format_date(UNIX_EPOCH), "1970-01-01");
+    }
+
+    #[test]
+    fn leap_days() {
+        // 2000 is a leap year, 1900 and 2100 aren't
+        assert_eq!(
+            parse_date("2000-02-29").map(format_date).as_deref(),
+            Ok("2000-02-29")
+        );
+        assert_eq!(
+            parse_date("2024-02-29").map(format_date).as_deref(),
+            Ok("2024-02-29")
+        );
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2100-02-29").is_err());
+        assert_eq!(
+            days_from_civil(2024, 3, 1) - days_from_civil(2024, 2, 28),
+            2
+        );
+        assert_eq!(
+            days_from_civil(2023, 3, 1) - days_from_civil(2023, 2, 28),
+            1
+        );
+    }
+
+    #[test]
+    fn year_boundaries() {
+        assert_eq!(
+            days_from_civil(2024, 1, 1) - days_from_civil(2023, 12, 31),
+            1
+        );
+        assert_eq!(
+            civil_from_days(days_from_civil(1999, 12, 31) + 1),
+            (2000, 1, 1)
+        );
+        let new_year = parse_date("2025-01-01").unwrap();
+        assert_eq!(format_date(new_year - DAY), "2024-12-31");
+        assert_eq!(
+            format_date(new_year + DAY - std::time::Duration::from_secs(1)),
+            "2025-01-01"
+        );
+    }
+
+    #[test]
+    fn round_trips_every_day() {
+        for days in 0..days_from_civil(2100, 1, 1) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for text in [
+            "",
+            "2024",
+            "2024-13-01",
+            "2024-04-31",
+            "2024-00-10",
+            "1969-12-31",
+        ] {
+            assert!(parse_date(text).is_err(), "{}", text);
+        }
+        // Too far out instead of overflowing
+        assert!(parse_date("99999999999-01-01").is_err());
+        assert_eq!(
+            parse_date("9999-12-31").map(format_date).as_deref(),
+            Ok("9999-12-31")
+        );
+    }
+}
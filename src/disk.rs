@@ -0,0 +1,26 @@
+use std::{io, path::Path};
+
+/// How much space the drive a scanned folder is on has.
+#[derive(Debug, Clone, Copy)]
+pub struct DiskSpace {
+    /// The bytes available to the current user.
+    pub free: u64,
+    pub total: u64,
+}
+
+impl DiskSpace {
+    /// Below this share of free space a drive counts as nearly full.
+    const NEARLY_FULL: f64 = 0.1;
+
+    /// Queries the space of the drive `path` is on.
+    pub fn of(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            free: fs2::available_space(path)?,
+            total: fs2::total_space(path)?,
+        })
+    }
+
+    pub fn is_nearly_full(&self) -> bool {
+        self.total > 0 && (self.free as f64) < self.total as f64 * Self::NEARLY_FULL
+    }
+}
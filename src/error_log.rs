@@ -0,0 +1,69 @@
+use std::io;
+
+use crate::scanner::ScanError;
+
+/// The errors of a scan, grouped by what went wrong.
+///
+/// A tree full of unreadable folders can produce millions of errors, so only the first
+/// [`ErrorLog::MAX_MESSAGES`] are kept in full. The counts per kind always cover all of them.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorLog {
+    errors: Vec<ScanError>,
+    /// How often each kind of error occurred, in the order they first occurred.
+    kinds: Vec<(io::ErrorKind, u64)>,
+    total: u64,
+}
+
+impl ErrorLog {
+    pub const MAX_MESSAGES: usize = 1_000;
+
+    pub fn push(&mut self, error: ScanError) {
+        self.total += 1;
+        match self
+            .kinds
+            .iter_mut()
+            .find(|(existing, _)| *existing == error.kind)
+        {
+            Some((_, count)) => *count += 1,
+            None => self.kinds.push((error.kind, 1)),
+        }
+        if self.errors.len() < Self::MAX_MESSAGES {
+            self.errors.push(error);
+        }
+    }
+
+    /// Adds an error which isn't about a particular path.
+    pub fn push_message(&mut self, kind: io::ErrorKind, message: String) {
+        self.push(ScanError::other(kind, message));
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// How many errors occurred, including those whose message wasn't kept.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// The kept errors, oldest first.
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
+    }
+
+    /// How many errors occurred whose message wasn't kept.
+    pub fn dropped(&self) -> u64 {
+        self.total - self.errors.len() as u64
+    }
+
+    /// Each kind of error with how often it occurred, the most common first.
+    pub fn kinds(&self) -> Vec<(io::ErrorKind, u64)> {
+        let mut kinds = self.kinds.clone();
+        kinds.sort_by(|(_, a), (_, b)| b.cmp(a));
+        kinds
+    }
+}
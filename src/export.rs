@@ -0,0 +1,584 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{self, Path},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs,
+    io::{AsyncWriteExt, BufWriter},
+};
+
+use crate::{
+    error_log::ErrorLog,
+    measure::LengthMetric,
+    scanner::OverLimit,
+    segments::Segment,
+    suggest::{name_lengths, suggest_name, suggest_path},
+};
+
+/// The structure of a JSON report.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonReport {
+    /// The folders that were scanned.
+    pub roots: Vec<String>,
+    pub limit: usize,
+    /// Paths over this are errors, those between it and the limit only warnings.
+    #[serde(default)]
+    pub error_limit: Option<usize>,
+    /// The unit the lengths are counted in.
+    pub metric: String,
+    /// The file extensions the scan was limited to. Empty means every file and folder.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Only paths modified on or after this day were reported, as `YYYY-MM-DD`.
+    #[serde(default)]
+    pub modified_since: Option<String>,
+    /// Only paths modified on or before this day were reported, as `YYYY-MM-DD`.
+    #[serde(default)]
+    pub modified_until: Option<String>,
+    /// Whether paths and lengths are relative to the scanned folder they were found in.
+    #[serde(default)]
+    pub relative_paths: bool,
+    /// How many levels below the scanned folders the deepest directory was.
+    #[serde(default)]
+    pub deepest_nesting: usize,
+    /// How long the scan took in seconds.
+    #[serde(default)]
+    pub duration_seconds: Option<f64>,
+    /// Whether the scan was aborted, so the report only covers part of the folders.
+    #[serde(default)]
+    pub aborted: bool,
+    /// When the report was created, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The folder names contributing the most length across the paths over the limit.
+    #[serde(default)]
+    pub common_segments: Vec<JsonSegment>,
+    pub paths: Vec<JsonPath>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonPath {
+    pub path: String,
+    pub length: u64,
+    /// The scanned folder the path was found in.
+    pub root: String,
+    /// `warning` or `error`, if the report has an error limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    /// The zip archive the path is an entry of, it would be extracted to the path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonSegment {
+    pub name: String,
+    pub length: usize,
+    /// How many paths over the limit contain it.
+    pub count: u64,
+}
+
+impl From<Segment> for JsonSegment {
+    fn from(segment: Segment) -> Self {
+        Self {
+            name: segment.name,
+            length: segment.length,
+            count: segment.count,
+        }
+    }
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// The character separating the columns of a CSV export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Delimiter {
+    /// What spreadsheet apps expect in locales using a decimal comma.
+    #[default]
+    Semicolon,
+    Comma,
+    /// Tab-separated values, which aren't quoted.
+    Tab,
+}
+
+impl Delimiter {
+    pub const ALL: [Delimiter; 3] = [Delimiter::Semicolon, Delimiter::Comma, Delimiter::Tab];
+
+    pub fn as_char(self) -> char {
+        match self {
+            Delimiter::Semicolon => ';',
+            Delimiter::Comma => ',',
+            Delimiter::Tab => '\t',
+        }
+    }
+
+    /// The file extension and save dialog filter name of files with this delimiter.
+    pub fn file_type(self) -> (&'static str, &'static str) {
+        match self {
+            Delimiter::Semicolon | Delimiter::Comma => ("csv", "CSV"),
+            Delimiter::Tab => ("tsv", "TSV"),
+        }
+    }
+
+    /// Formats `value` as a text field.
+    ///
+    /// Separated by commas or semicolons, fields are quoted. Tab-separated values have no
+    /// quoting, so tabs and line breaks are replaced with spaces instead.
+    fn field(self, value: &str) -> String {
+        match self {
+            Delimiter::Semicolon | Delimiter::Comma => format!("\"{}\"", escape_csv(value)),
+            Delimiter::Tab => value.replace(['\t', '\r', '\n'], " "),
+        }
+    }
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Delimiter::Semicolon => "Semicolon (;)",
+            Delimiter::Comma => "Comma (,)",
+            Delimiter::Tab => "Tab (TSV)",
+        })
+    }
+}
+
+/// How the separators of paths are shown and exported.
+///
+/// Only the text changes, lengths are still measured on the paths as found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Separators {
+    /// As found, which can be mixed on mounted foreign file systems.
+    #[default]
+    Raw,
+    /// The separator of the system the app runs on.
+    System,
+    Slash,
+    Backslash,
+}
+
+impl Separators {
+    pub const ALL: [Separators; 4] = [
+        Separators::Raw,
+        Separators::System,
+        Separators::Slash,
+        Separators::Backslash,
+    ];
+
+    /// Replaces the separators of `path`.
+    pub fn apply(self, path: Cow<'_, str>) -> Cow<'_, str> {
+        let separator = match self {
+            Separators::Raw => return path,
+            Separators::System => path::MAIN_SEPARATOR,
+            Separators::Slash => '/',
+            Separators::Backslash => '\\',
+        };
+        let other = if separator == '/' { '\\' } else { '/' };
+        if path.contains(other) {
+            Cow::Owned(path.replace(other, &separator.to_string()))
+        } else {
+            path
+        }
+    }
+}
+
+impl fmt::Display for Separators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Separators::Raw => "As found",
+            Separators::System => "System style",
+            Separators::Slash => "Slashes (/)",
+            Separators::Backslash => "Backslashes (\\)",
+        })
+    }
+}
+
+/// Columns and units of a CSV export.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub limit: usize,
+    pub metric: LengthMetric,
+    /// Adds a shortened file name, see [`suggest_name`].
+    pub suggested_names: bool,
+    /// Adds the length of the base name and the extension as separate columns.
+    pub name_lengths: bool,
+    /// Adds the scanned folder each path was found in, for reports covering several folders.
+    pub roots: bool,
+    /// Adds whether each path is a warning or an error, see [`OverLimit::severity`].
+    pub error_limit: Option<usize>,
+    /// Adds a shortened path, see [`suggest_path`]. Unlike the suggested name, this shortens the
+    /// folders and keeps the file name.
+    pub suggested_paths: bool,
+    /// Writes paths and their lengths relative to the scanned folder they were found in.
+    ///
+    /// The overage and the suggested name still refer to the full path, which the limit applies to.
+    pub relative: bool,
+    pub delimiter: Delimiter,
+    pub separators: Separators,
+}
+
+/// Writes `paths` to a CSV file.
+///
+/// Rows are streamed through a buffered writer, so memory use doesn't grow with the number of results.
+pub async fn write_csv(
+    file_path: &Path,
+    paths: &[OverLimit],
+    options: &CsvOptions,
+) -> Result<(), String> {
+    let mut writer = CsvWriter::create(file_path, options.clone()).await?;
+    writer.write_rows(paths).await
+}
+
+/// A CSV file that rows are added to while a scan is running.
+///
+/// Every batch is flushed right away, so the file stays valid if the scan is aborted or the app
+/// crashes.
+pub struct CsvWriter {
+    writer: BufWriter<fs::File>,
+    options: CsvOptions,
+    row: String,
+}
+
+impl CsvWriter {
+    /// Creates the file and writes the header.
+    pub async fn create(file_path: &Path, options: CsvOptions) -> Result<Self, String> {
+        let file = fs::File::create(file_path)
+            .await
+            .map_err(|e| format!("Failed to create CSV file: {}", e))?;
+        let mut writer = BufWriter::new(file);
+
+        let d = options.delimiter.as_char();
+        let mut header = format!(
+            "Length ({}{}){d}Path{d}Overage",
+            options.metric,
+            if options.relative { ", relative" } else { "" }
+        );
+        // The headers name how the suggestions are made, as they can differ a lot
+        if options.suggested_names {
+            header.push_str(&format!("{d}Suggested Name (end of file name trimmed)"));
+        }
+        header.push_str(&format!("{d}File Size (Bytes)"));
+        if options.name_lengths {
+            header.push_str(&format!("{d}Base Name Length{d}Extension Length"));
+        }
+        if options.roots {
+            header.push_str(&format!("{d}Folder"));
+        }
+        if options.suggested_paths {
+            header.push_str(&format!("{d}Suggested Path (longest folder names trimmed)"));
+        }
+        if let Some(error_limit) = options.error_limit {
+            header.push_str(&format!("{d}Severity (Error over {})", error_limit));
+        }
+        header.push('\n');
+        writer
+            .write_all(header.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write CSV header: {}", e))?;
+
+        Ok(Self {
+            writer,
+            options,
+            row: String::new(),
+        })
+    }
+
+    /// Appends a row for each of `paths` and flushes them to the file.
+    pub async fn write_rows(&mut self, paths: &[OverLimit]) -> Result<(), String> {
+        let options = &self.options;
+        let delimiter = options.delimiter;
+        let d = delimiter.as_char();
+        for path in paths {
+            let (length, shown_path) = if options.relative {
+                let relative = path.relative_path();
+                (
+                    options.metric.measure(Path::new(&*relative)) as u64,
+                    relative,
+                )
+            } else {
+                (path.size, Cow::Borrowed(path.path.as_str()))
+            };
+            let shown_path = options.separators.apply(shown_path);
+
+            self.row.clear();
+            self.row.push_str(&format!(
+                "{}{d}{}{d}{}",
+                length,
+                delimiter.field(&shown_path),
+                path.size.saturating_sub(options.limit as u64),
+            ));
+            if options.suggested_names {
+                let suggested = suggest_name(Path::new(&path.path), options.limit, options.metric)
+                    .unwrap_or_default();
+                self.row
+                    .push_str(&format!("{d}{}", delimiter.field(&suggested)));
+            }
+            self.row.push_str(&format!("{d}{}", path.file_bytes));
+            if options.name_lengths {
+                let (base, extension) = name_lengths(Path::new(&path.path), options.metric);
+                self.row.push_str(&format!("{d}{}{d}{}", base, extension));
+            }
+            if options.roots {
+                self.row
+                    .push_str(&format!("{d}{}", delimiter.field(&path.root)));
+            }
+            if options.suggested_paths {
+                let suggested = suggest_path(Path::new(&path.path), options.limit, options.metric)
+                    .unwrap_or_default();
+                self.row
+                    .push_str(&format!("{d}{}", delimiter.field(&suggested)));
+            }
+            if let Some(severity) = path.severity(options.error_limit) {
+                self.row.push_str(&format!("{d}{}", severity.as_str()));
+            }
+            self.row.push('\n');
+
+            self.writer
+                .write_all(self.row.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to write CSV row: {}", e))?;
+        }
+
+        self.writer
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to flush CSV file: {}", e))
+    }
+}
+
+/// Writes `report` as a Markdown table below a short summary, for pasting into issues or docs.
+pub async fn write_markdown(file_path: &Path, report: &JsonReport) -> Result<(), String> {
+    let mut content = String::from("# Path Length Report\n\n");
+    for root in &report.roots {
+        content.push_str(&format!("- Scanned: {}\n", escape_markdown(root)));
+    }
+    content.push_str(&format!(
+        "- Limit: {} ({})\n- Paths over the limit: {}\n",
+        report.limit,
+        report.metric,
+        report.paths.len()
+    ));
+    if let Some(error_limit) = report.error_limit {
+        content.push_str(&format!("- Error limit: {}\n", error_limit));
+    }
+    if report.modified_since.is_some() || report.modified_until.is_some() {
+        content.push_str(&format!(
+            "- Modified: {} to {}\n",
+            report.modified_since.as_deref().unwrap_or("any time"),
+            report.modified_until.as_deref().unwrap_or("now")
+        ));
+    }
+    if report.aborted {
+        content.push_str("- The scan was aborted, so these are only part of the results\n");
+    }
+
+    let severities = report.error_limit.is_some();
+    content.push_str(if severities {
+        "\n| Length | Path | Severity |\n|---:|---|---|\n"
+    } else {
+        "\n| Length | Path |\n|---:|---|\n"
+    });
+    for path in &report.paths {
+        content.push_str(&format!(
+            "| {} | {} |",
+            path.length,
+            escape_markdown(&path.path)
+        ));
+        if severities {
+            content.push_str(&format!(
+                " {} |",
+                path.severity.as_deref().unwrap_or_default()
+            ));
+        }
+        content.push('\n');
+    }
+
+    fs::write(file_path, content)
+        .await
+        .map_err(|e| format!("Failed to write Markdown file: {}", e))
+}
+
+/// Writes a summary of the errors followed by each kept message to a text file.
+pub async fn write_errors(file_path: &Path, errors: &ErrorLog) -> Result<(), String> {
+    let mut content = format!("{} errors\n", errors.total());
+    for (kind, count) in errors.kinds() {
+        content.push_str(&format!("{}: {}\n", kind, count));
+    }
+    content.push('\n');
+    for error in errors.errors() {
+        content.push_str(&error.to_string());
+        content.push('\n');
+    }
+    if errors.dropped() > 0 {
+        content.push_str(&format!(
+            "{} more errors weren't kept to save memory\n",
+            errors.dropped()
+        ));
+    }
+
+    fs::write(file_path, content)
+        .await
+        .map_err(|e| format!("Failed to write error report: {}", e))
+}
+
+/// Escapes what Markdown would otherwise format, so paths show up as they are.
+///
+/// Pipes would also end a table cell.
+fn escape_markdown(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(
+            c,
+            '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape_csv(value: &str) -> String {
+    value.replace("\\", "\\\\").replace("\"", "\"\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::TempDir;
+
+    use super::{CsvOptions, CsvWriter, Delimiter, JsonPath, JsonReport, JsonSegment, Separators};
+    use crate::{measure::LengthMetric, scanner::OverLimit};
+
+    fn over_limit(path: String, file_bytes: u64) -> OverLimit {
+        OverLimit {
+            size: path.len() as u64,
+            path,
+            file_bytes,
+            is_dir: false,
+            root: Arc::from("/data"),
+            archive: None,
+        }
+    }
+
+    /// Tab-separated, so the columns can be split without unquoting.
+    fn csv_options(limit: usize) -> CsvOptions {
+        CsvOptions {
+            limit,
+            metric: LengthMetric::Bytes,
+            suggested_names: true,
+            name_lengths: false,
+            roots: false,
+            error_limit: None,
+            suggested_paths: false,
+            relative: false,
+            delimiter: Delimiter::Tab,
+            separators: Separators::Raw,
+        }
+    }
+
+    #[test]
+    fn json_report_round_trips() {
+        let report = JsonReport {
+            roots: vec![r"C:\Data".to_string(), "/home/user/Überordner".to_string()],
+            limit: 240,
+            error_limit: Some(260),
+            metric: "UTF-16 code units".to_string(),
+            extensions: vec!["pdf".to_string()],
+            modified_since: Some("2024-01-01".to_string()),
+            modified_until: None,
+            relative_paths: false,
+            deepest_nesting: 12,
+            duration_seconds: Some(1.5),
+            aborted: true,
+            timestamp: 1_700_000_000,
+            common_segments: vec![JsonSegment {
+                name: "Projekte \"alt\"".to_string(),
+                length: 14,
+                count: 2,
+            }],
+            paths: vec![
+                JsonPath {
+                    path: r#"C:\Data\Projekte "alt"\Bericht; Entwurf.docx"#.to_string(),
+                    length: 43,
+                    root: r"C:\Data".to_string(),
+                    severity: Some("warning".to_string()),
+                    archive: None,
+                },
+                JsonPath {
+                    path: "/home/user/Überordner/emoji 😀/tab\tand\nnewline".to_string(),
+                    length: 48,
+                    root: "/home/user/Überordner".to_string(),
+                    severity: None,
+                    archive: Some("/home/user/Überordner/archive.zip".to_string()),
+                },
+            ],
+        };
+
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: JsonReport = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, report);
+    }
+
+    #[tokio::test]
+    async fn writes_many_rows() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("report.tsv");
+        let paths: Vec<OverLimit> = (0..100_000)
+            .map(|i| over_limit(format!("/data/file{:05}.txt", i), i))
+            .collect();
+
+        let mut writer = CsvWriter::create(&file, csv_options(17)).await.unwrap();
+        // In batches, the way a running scan writes them. Each batch is on disk before the next
+        // one is written, so nothing piles up in memory.
+        for (index, batch) in paths.chunks(10_000).enumerate() {
+            writer.write_rows(batch).await.unwrap();
+            let written = std::fs::read_to_string(&file).unwrap().lines().count();
+            assert_eq!(written, 1 + (index + 1) * 10_000);
+            assert!(writer.row.capacity() < 1024);
+        }
+        drop(writer);
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 100_001);
+        assert!(lines[0].starts_with("Length (Bytes)\tPath\tOverage"));
+        assert_eq!(
+            lines[100_000],
+            "19\t/data/file99999.txt\t2\tfile999.txt\t99999"
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_how_far_over_the_limit_paths_are() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("report.tsv");
+        // 16 and 12 bytes long
+        let paths = [
+            over_limit("/data/longer.txt".to_string(), 0),
+            over_limit("/data/at.txt".to_string(), 0),
+        ];
+
+        super::write_csv(&file, &paths, &csv_options(12))
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        let overages: Vec<&str> = content
+            .lines()
+            .map(|line| line.split('\t').nth(2).unwrap())
+            .collect();
+        assert_eq!(overages, ["Overage", "4", "0"]);
+    }
+}
@@ -0,0 +1,45 @@
+/// How many paths of each length a scan found.
+///
+/// Only the lengths are kept, so this stays small no matter how many paths are scanned.
+#[derive(Debug, Clone, Default)]
+pub struct LengthHistogram {
+    /// The number of paths for each length, indexed by the length.
+    counts: Vec<u64>,
+}
+
+impl LengthHistogram {
+    /// Lengths above this are counted as this length.
+    ///
+    /// It's the longest path Windows supports, even with the extended-length prefix.
+    pub const MAX_LENGTH: usize = 32_767;
+
+    pub fn record(&mut self, length: usize) {
+        let length = length.min(Self::MAX_LENGTH);
+        if self.counts.len() <= length {
+            self.counts.resize(length + 1, 0);
+        }
+        self.counts[length] += 1;
+    }
+
+    pub fn merge(&mut self, other: &LengthHistogram) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+    }
+
+    /// Returns how many paths are longer than `limit`.
+    pub fn count_over(&self, limit: usize) -> u64 {
+        self.counts.iter().skip(limit.saturating_add(1)).sum()
+    }
+
+    /// Returns the length of the longest path, or 0 if there are none.
+    pub fn max_length(&self) -> usize {
+        self.counts
+            .iter()
+            .rposition(|count| *count > 0)
+            .unwrap_or_default()
+    }
+}
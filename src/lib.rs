@@ -0,0 +1,20 @@
+#[cfg(feature = "zip")]
+mod archive;
+mod baseline;
+pub mod cli;
+mod date;
+mod disk;
+mod error_log;
+mod export;
+mod histogram;
+pub mod measure;
+mod memory;
+mod project;
+mod reveal;
+pub mod scanner;
+mod segments;
+mod serve;
+mod settings;
+mod suggest;
+mod top;
+pub mod ui;
@@ -1,11 +1,24 @@
 #![windows_subsystem = "windows"]
 
+use std::process::ExitCode;
+
+use clap::Parser;
 use iced::application;
 
-use crate::ui::UI;
+use path_length_checker::{cli, ui::UI};
 
-mod ui;
+fn main() -> ExitCode {
+    // Without any arguments the window opens as usual
+    if std::env::args_os().len() > 1 {
+        cli::attach_console();
+        return cli::run(cli::Args::parse());
+    }
 
-fn main() {
-    application(UI::start, UI::update, UI::view).run().unwrap();
+    application(UI::start, UI::update, UI::view)
+        .subscription(UI::subscription)
+        .theme(UI::theme)
+        .window(UI::window_settings())
+        .run()
+        .unwrap();
+    ExitCode::SUCCESS
 }
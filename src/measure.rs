@@ -0,0 +1,183 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// The unit a path length is counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum LengthMetric {
+    Bytes,
+    Chars,
+    Utf16,
+}
+
+impl LengthMetric {
+    pub const ALL: [LengthMetric; 3] = [
+        LengthMetric::Bytes,
+        LengthMetric::Chars,
+        LengthMetric::Utf16,
+    ];
+
+    /// Returns the length of `path` in this unit.
+    ///
+    /// An extended-length prefix isn't counted, see [`strip_verbatim_prefix`].
+    pub fn measure(self, path: &Path) -> usize {
+        let path = strip_verbatim_prefix(path);
+        match self {
+            LengthMetric::Bytes => path.as_os_str().len(),
+            LengthMetric::Chars => path.as_os_str().to_string_lossy().chars().count(),
+            LengthMetric::Utf16 => {
+                #[cfg(windows)]
+                {
+                    use std::os::windows::ffi::OsStrExt;
+                    path.as_os_str().encode_wide().count()
+                }
+                #[cfg(not(windows))]
+                {
+                    path.as_os_str().to_string_lossy().encode_utf16().count()
+                }
+            }
+        }
+    }
+
+    /// Like [`LengthMetric::measure`], but composes `path` to Unicode NFC first.
+    ///
+    /// Some file systems, like the one of macOS, store names decomposed (NFD), so an accented
+    /// letter counts as two characters. Composed, lengths are comparable between systems.
+    /// Paths which aren't valid Unicode are measured as they are.
+    pub fn measure_nfc(self, path: &Path) -> usize {
+        let path = strip_verbatim_prefix(path);
+        match path.to_str() {
+            Some(text) => {
+                let composed: String = text.nfc().collect();
+                self.measure(Path::new(&composed))
+            }
+            None => self.measure(&path),
+        }
+    }
+
+    /// Returns how much a single character contributes to [`LengthMetric::measure`].
+    pub fn char_length(self, c: char) -> usize {
+        match self {
+            LengthMetric::Bytes => c.len_utf8(),
+            LengthMetric::Chars => 1,
+            LengthMetric::Utf16 => c.len_utf16(),
+        }
+    }
+}
+
+impl Default for LengthMetric {
+    /// Counts the way the current operating system does against its limits.
+    ///
+    /// Windows limits paths in UTF-16 code units, other systems limit them in bytes.
+    fn default() -> Self {
+        if cfg!(windows) {
+            LengthMetric::Utf16
+        } else {
+            LengthMetric::Bytes
+        }
+    }
+}
+
+impl fmt::Display for LengthMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LengthMetric::Bytes => "Bytes",
+            LengthMetric::Chars => "Characters",
+            LengthMetric::Utf16 => "UTF-16 code units",
+        })
+    }
+}
+
+/// Returns the drive `path` is stored on, so results can be grouped by it.
+///
+/// On Windows this is the drive letter or network share. Other systems have no drives, so the
+/// top-level folder is used instead, which is where other drives are usually mounted.
+pub fn drive(path: &Path) -> String {
+    let path = strip_verbatim_prefix(path);
+    let mut components = path.components();
+    match components.next() {
+        Some(Component::Prefix(prefix)) => prefix.as_os_str().to_string_lossy().to_string(),
+        Some(Component::RootDir) => match components.next() {
+            Some(Component::Normal(name)) => format!("/{}", name.to_string_lossy()),
+            _ => "/".to_string(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Removes the Windows extended-length prefix from `path`.
+///
+/// `\\?\C:\Data` becomes `C:\Data` and `\\?\UNC\server\share` becomes `\\server\share`.
+/// The prefix is what lets a program go past the length limit, so the limit applies to the path
+/// without it. This is also how the path is usually shown. Does nothing on other systems.
+pub fn strip_verbatim_prefix(path: &Path) -> Cow<'_, Path> {
+    if !cfg!(windows) {
+        return Cow::Borrowed(path);
+    }
+    let Some(text) = path.to_str() else {
+        return Cow::Borrowed(path);
+    };
+
+    if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+        Cow::Owned(PathBuf::from(format!(r"\\{}", rest)))
+    } else if let Some(rest) = text.strip_prefix(r"\\?\")
+        && rest.as_bytes().get(1) == Some(&b':')
+    {
+        Cow::Borrowed(Path::new(rest))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::LengthMetric;
+
+    #[test]
+    fn metrics_count_differently() {
+        // 2 bytes for é, 1 for the separator and 4 for the emoji, which is 2 UTF-16 code units
+        let path = Path::new("é/😀");
+        assert_eq!(LengthMetric::Bytes.measure(path), 7);
+        assert_eq!(LengthMetric::Chars.measure(path), 3);
+        assert_eq!(LengthMetric::Utf16.measure(path), 4);
+    }
+
+    #[test]
+    fn char_length_matches_measure() {
+        for metric in LengthMetric::ALL {
+            let summed: usize = "é/😀".chars().map(|c| metric.char_length(c)).sum();
+            assert_eq!(summed, metric.measure(Path::new("é/😀")), "{}", metric);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn strips_the_verbatim_prefix() {
+        use super::strip_verbatim_prefix;
+
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\C:\Data\file.txt")),
+            Path::new(r"C:\Data\file.txt")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"\\?\UNC\server\share\file.txt")),
+            Path::new(r"\\server\share\file.txt")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(Path::new(r"C:\Data\file.txt")),
+            Path::new(r"C:\Data\file.txt")
+        );
+        assert_eq!(
+            LengthMetric::Utf16.measure(Path::new(r"\\?\C:\Data")),
+            LengthMetric::Utf16.measure(Path::new(r"C:\Data"))
+        );
+    }
+}
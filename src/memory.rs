@@ -0,0 +1,30 @@
+/// Returns the resident memory of the current process in bytes, if it can be determined.
+#[cfg(target_os = "linux")]
+pub fn resident_set_size() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Returns the resident memory of the current process in bytes, if it can be determined.
+#[cfg(windows)]
+pub fn resident_set_size() -> Option<u64> {
+    use windows_sys::Win32::System::{
+        ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+        Threading::GetCurrentProcess,
+    };
+
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    // SAFETY: the counters are plain data and the pseudo handle of the current process is always valid
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    let success = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+
+    (success != 0).then_some(counters.WorkingSetSize as u64)
+}
+
+/// Returns the resident memory of the current process in bytes, if it can be determined.
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn resident_set_size() -> Option<u64> {
+    None
+}
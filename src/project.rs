@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use tokio::fs;
+
+/// Name of the file a project can place in its root to pin the path length limit.
+pub const LIMIT_FILE: &str = ".pathlen-limit";
+
+/// Name of the file a project can place in its root to skip files and folders, like `.gitignore`.
+pub const IGNORE_FILE: &str = ".plcignore";
+
+/// Reads the limit pinned by the project marker file in `root`, if there is one.
+///
+/// The file should contain a single positive number. Anything else is ignored.
+pub async fn read_limit(root: &Path) -> Option<usize> {
+    let content = fs::read_to_string(root.join(LIMIT_FILE)).await.ok()?;
+    content
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|limit| *limit > 0)
+}
+
+/// Reads the patterns of the ignore file in `root`, if there is one, along with the lines which
+/// aren't valid patterns.
+///
+/// Every line is a glob pattern. Empty lines and lines starting with `#` are skipped. Patterns with
+/// a `/` are relative to `root`, others match names anywhere below it. A trailing `/` is dropped,
+/// so such a pattern matches files of that name too. Negated patterns starting with `!` aren't
+/// supported.
+pub async fn read_ignore(root: &Path) -> (Vec<glob::Pattern>, Vec<String>) {
+    let file = root.join(IGNORE_FILE);
+    let Ok(content) = fs::read_to_string(&file).await else {
+        return Default::default();
+    };
+
+    let mut patterns = Vec::new();
+    let mut invalid = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |reason: &str| {
+            format!(
+                "Invalid pattern on line {} of {}: {}",
+                index + 1,
+                file.display(),
+                reason
+            )
+        };
+        if line.starts_with('!') {
+            invalid.push(error("negated patterns aren't supported"));
+            continue;
+        }
+
+        let line = line.trim_end_matches('/');
+        let pattern = match line.strip_prefix('/') {
+            Some(relative) => anchor(root, relative),
+            None if line.contains('/') && !line.starts_with("**/") => anchor(root, line),
+            None => line.to_string(),
+        };
+        match glob::Pattern::new(&pattern) {
+            Ok(pattern) => patterns.push(pattern),
+            Err(err) => invalid.push(error(&err.to_string())),
+        }
+    }
+    (patterns, invalid)
+}
+
+/// Makes `relative` a pattern for the full paths below `root`.
+fn anchor(root: &Path, relative: &str) -> String {
+    format!(
+        "{}/{}",
+        glob::Pattern::escape(&root.to_string_lossy()),
+        relative
+    )
+}
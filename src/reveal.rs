@@ -0,0 +1,29 @@
+use std::{io, path::Path, process::Command};
+
+/// Opens the system file manager at the folder containing `path`.
+///
+/// Where the file manager supports it, `path` itself is selected.
+pub fn reveal(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // Explorer doesn't understand the quoting Rust applies to an argument with spaces
+        Command::new("explorer")
+            .raw_arg(format!("/select,\"{}\"", path.display()))
+            .spawn()?;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Command::new("open").arg("-R").arg(path).spawn()?;
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let folder = path.parent().unwrap_or(path);
+        Command::new("xdg-open").arg(folder).spawn()?;
+    }
+
+    Ok(())
+}
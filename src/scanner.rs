@@ -0,0 +1,1395 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::Metadata,
+    io, mem,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use iced::futures::{
+    FutureExt, SinkExt, Stream, StreamExt,
+    channel::mpsc,
+    stream::{self, FuturesUnordered},
+};
+use tokio::{
+    fs,
+    sync::watch,
+    time::{Instant, sleep},
+};
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "zip")]
+use crate::archive;
+use crate::{
+    date,
+    histogram::LengthHistogram,
+    measure::{LengthMetric, strip_verbatim_prefix},
+    project,
+};
+
+/// Settings which control a single scan.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    pub limit: usize,
+    pub metric: LengthMetric,
+    /// Measures paths composed to Unicode NFC, see [`LengthMetric::measure_nfc`].
+    pub normalize_unicode: bool,
+    pub recurse: bool,
+    /// Descends into symbolic links to directories. Each directory is only visited once,
+    /// so link cycles don't make the scan run forever.
+    pub follow_symlinks: bool,
+    /// How many levels of subdirectories to descend into. `None` means no limit.
+    pub max_depth: Option<usize>,
+    /// Also checks the scanned folders themselves, not only what's inside them.
+    pub include_roots: bool,
+    /// Directories matching any of these by name or full path are skipped entirely.
+    pub excludes: Vec<glob::Pattern>,
+    /// Only entries matching any of these by name or full path are reported. Empty reports
+    /// everything. Directories are still traversed when they don't match, but excluded
+    /// directories aren't, so excludes win over includes.
+    pub includes: Vec<glob::Pattern>,
+    /// Files and directories matching any of these by name or full path are skipped entirely.
+    /// Filled from the ignore files of the scanned folders, see [`ScanOptions::load_ignore_files`].
+    /// Like excludes, they win over includes.
+    pub ignores: Vec<glob::Pattern>,
+    /// Skips everything whose name starts with a dot, and on Windows also everything with the
+    /// hidden attribute. Hidden directories aren't descended into.
+    pub skip_hidden: bool,
+    /// Only files with one of these extensions are reported, see [`normalize_extension`].
+    /// Empty reports every file and directory.
+    pub extensions: Vec<String>,
+    /// Only entries modified on or after the start of this day are reported.
+    pub modified_since: Option<SystemTime>,
+    /// Only entries modified before the end of this day are reported.
+    pub modified_until: Option<SystemTime>,
+    /// Looks for entries whose names only differ by case, which can't all exist on a
+    /// case-insensitive file system.
+    pub detect_case_collisions: bool,
+    /// Also reports the entries of zip archives whose path would be over the limit once
+    /// extracted next to the archive. Needs the `zip` feature, without it this does nothing.
+    pub scan_archives: bool,
+    /// How many directories are read at the same time.
+    pub workers: usize,
+    /// How often progress is reported.
+    pub update_interval: Duration,
+    /// Progress is also reported as soon as this many new paths over the limit were found.
+    pub batch_size: usize,
+    /// How often reading a directory or its entries is tried again before giving up, for
+    /// errors which may go away, like a network share briefly dropping out.
+    pub retries: u32,
+    /// What a count before the scan found, see [`count_entries`]. Used to size the bookkeeping
+    /// of the scan up front.
+    pub expected: Option<EntryCount>,
+}
+
+impl ScanOptions {
+    pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+    pub const DEFAULT_BATCH_SIZE: usize = 1_000;
+    pub const DEFAULT_RETRIES: u32 = 2;
+    /// How long to wait before the first retry, each further retry waits twice as long.
+    const RETRY_DELAY: Duration = Duration::from_millis(50);
+
+    /// Options for a recursive scan reporting every path over `limit`, without any filters.
+    pub fn new(limit: usize, metric: LengthMetric) -> Self {
+        Self {
+            limit,
+            metric,
+            normalize_unicode: false,
+            recurse: true,
+            follow_symlinks: false,
+            max_depth: None,
+            include_roots: false,
+            excludes: Vec::new(),
+            includes: Vec::new(),
+            ignores: Vec::new(),
+            skip_hidden: false,
+            extensions: Vec::new(),
+            modified_since: None,
+            modified_until: None,
+            detect_case_collisions: false,
+            scan_archives: false,
+            workers: Self::default_workers(),
+            update_interval: Self::DEFAULT_UPDATE_INTERVAL,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            retries: Self::DEFAULT_RETRIES,
+            expected: None,
+        }
+    }
+
+    /// Returns a worker count matching the available CPU cores.
+    pub fn default_workers() -> usize {
+        thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(4)
+    }
+
+    /// Returns the length of `path`, composed to NFC first if [`ScanOptions::normalize_unicode`]
+    /// is set.
+    pub fn measure(&self, path: &Path) -> usize {
+        if self.normalize_unicode {
+            self.metric.measure_nfc(path)
+        } else {
+            self.metric.measure(path)
+        }
+    }
+
+    /// Whether subdirectories of a directory at `depth` should be scanned.
+    pub fn descends_into(&self, depth: usize) -> bool {
+        self.recurse && self.max_depth.is_none_or(|max_depth| depth < max_depth)
+    }
+
+    /// Reads the metadata of `entry`, of the link target if symbolic links are followed.
+    async fn metadata(&self, entry: &fs::DirEntry) -> io::Result<Metadata> {
+        if self.follow_symlinks {
+            fs::metadata(entry.path()).await
+        } else {
+            entry.metadata().await
+        }
+    }
+
+    /// Runs `operation` again if it fails with an error which may go away, up to
+    /// [`ScanOptions::retries`] times.
+    async fn retry<T>(&self, mut operation: impl AsyncFnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Err(err) if attempt < self.retries && is_transient(&err) => {
+                    sleep(Self::RETRY_DELAY * 2u32.saturating_pow(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// The key `path` is tracked by to visit each directory only once.
+    ///
+    /// Only needed when following symbolic links, as the tree can't contain cycles otherwise.
+    async fn visit_key(&self, path: &Path) -> Option<PathBuf> {
+        if self.follow_symlinks {
+            fs::canonicalize(path).await.ok()
+        } else {
+            None
+        }
+    }
+
+    /// Whether `path` should be measured and reported if it's over the limit.
+    ///
+    /// Directories are still traversed when they aren't reported.
+    pub fn is_reported(&self, path: &Path, is_dir: bool) -> bool {
+        if !self.includes.is_empty() && !matches_any(&self.includes, path) {
+            return false;
+        }
+        if self.extensions.is_empty() {
+            return true;
+        }
+        !is_dir
+            && path.extension().is_some_and(|extension| {
+                let extension = extension.to_string_lossy().to_lowercase();
+                self.extensions.contains(&extension)
+            })
+    }
+
+    /// Whether `path` should be skipped because it's hidden.
+    ///
+    /// `metadata` is needed for the hidden attribute on Windows, without it only the name is
+    /// checked.
+    pub fn is_hidden(&self, path: &Path, metadata: Option<&Metadata>) -> bool {
+        if !self.skip_hidden {
+            return false;
+        }
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            return true;
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::MetadataExt;
+            use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_HIDDEN;
+
+            if metadata
+                .is_some_and(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+            {
+                return true;
+            }
+        }
+        #[cfg(not(windows))]
+        let _ = metadata;
+        false
+    }
+
+    /// Whether an entry with `metadata` was modified within the date range, if one is set.
+    ///
+    /// Directories are still traversed when they aren't reported, so recently modified files
+    /// in old folders are found too.
+    pub fn is_modified_in_range(&self, metadata: Option<&Metadata>) -> bool {
+        if self.modified_since.is_none() && self.modified_until.is_none() {
+            return true;
+        }
+        let Some(modified) = metadata.and_then(|metadata| metadata.modified().ok()) else {
+            return false;
+        };
+        self.modified_since.is_none_or(|since| modified >= since)
+            && self
+                .modified_until
+                // The whole last day counts, up to the end of time
+                .is_none_or(|until| {
+                    until
+                        .checked_add(date::DAY)
+                        .is_none_or(|end| modified < end)
+                })
+    }
+
+    /// Whether `path` should be skipped because of an exclude or ignore pattern.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        (is_dir && matches_any(&self.excludes, path)) || matches_any(&self.ignores, path)
+    }
+
+    /// Adds the patterns of the [`project::IGNORE_FILE`] of each of `roots` to the ignores.
+    ///
+    /// Returns the lines which couldn't be used.
+    pub async fn load_ignore_files(&mut self, roots: &[PathBuf]) -> Vec<String> {
+        let mut invalid = Vec::new();
+        for root in roots {
+            let (patterns, errors) = project::read_ignore(root).await;
+            self.ignores.extend(patterns);
+            invalid.extend(errors);
+        }
+        invalid
+    }
+}
+
+/// Whether any of `patterns` matches the name or the full path of `path`.
+fn matches_any(patterns: &[glob::Pattern], path: &Path) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy());
+    patterns.iter().any(|pattern| {
+        pattern.matches_path(path) || name.as_ref().is_some_and(|name| pattern.matches(name))
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct OverLimit {
+    pub path: String,
+    pub size: u64,
+    /// The size of the file in bytes, 0 for directories.
+    pub file_bytes: u64,
+    pub is_dir: bool,
+    /// The scanned folder this path was found in.
+    pub root: Arc<str>,
+    /// The zip archive this is an entry of, see [`ScanOptions::scan_archives`]. The path is
+    /// where the entry would be extracted to, it doesn't exist yet.
+    pub archive: Option<Arc<str>>,
+}
+
+/// How far over the limit a path is, once a separate error limit is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Over the limit, but not over the error limit.
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl OverLimit {
+    /// Returns how far over the limit the path is, or `None` without an error limit.
+    pub fn severity(&self, error_limit: Option<usize>) -> Option<Severity> {
+        error_limit.map(|error_limit| {
+            if self.size > error_limit as u64 {
+                Severity::Error
+            } else {
+                Severity::Warning
+            }
+        })
+    }
+
+    /// The path relative to the scanned folder it was found in.
+    pub fn relative_path(&self) -> Cow<'_, str> {
+        Path::new(&self.path)
+            .strip_prefix(&*self.root)
+            .ok()
+            // The scanned folder itself is shown in full
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map_or(Cow::Borrowed(self.path.as_str()), |relative| {
+                relative.to_string_lossy()
+            })
+    }
+}
+
+/// Entries in the same directory whose names only differ by case.
+#[derive(Debug, Clone)]
+pub struct CaseCollision {
+    /// The full paths of the colliding entries, sorted.
+    pub paths: Vec<String>,
+}
+
+/// Brings an extension as typed by the user, like `.PNG` or `*.png`, into the form
+/// [`ScanOptions::extensions`] expects, like `png`.
+pub fn normalize_extension(extension: &str) -> String {
+    extension
+        .trim()
+        .trim_start_matches('*')
+        .trim_start_matches('.')
+        .to_lowercase()
+}
+
+/// What a running scan reports to its caller.
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    Progress(ScanProgress),
+    /// A directory or entry couldn't be read. The scan skips it and carries on.
+    Error(ScanError),
+}
+
+/// Something that went wrong, kept apart so it can be grouped, linked to and formatted later.
+#[derive(Debug, Clone)]
+pub struct ScanError {
+    /// What went wrong, to group similar errors.
+    pub kind: io::ErrorKind,
+    pub action: ErrorAction,
+    /// The directory or entry the error is about, if it's about one.
+    pub path: Option<PathBuf>,
+    /// The system's description of the error.
+    pub message: String,
+}
+
+/// What failed when an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    ReadDirectory,
+    ReadMetadata,
+    ReadArchive,
+    /// Anything outside of reading the scanned folders, like an export.
+    Other,
+}
+
+impl ScanError {
+    /// An error reading `path`.
+    ///
+    /// The system's message for a path that is too long to open is quite cryptic, so it gets
+    /// explained, along with the length of the path as the scan measures it.
+    fn io(action: ErrorAction, path: PathBuf, err: &io::Error, options: &ScanOptions) -> Self {
+        let mut message = err.to_string();
+        if is_too_long(err, &path) {
+            message.push_str(&format!(
+                " (the path is {} {} long, which is too long for the system to open)",
+                options.measure(&path),
+                options.metric
+            ));
+        }
+        Self {
+            kind: err.kind(),
+            action,
+            path: Some(path),
+            message,
+        }
+    }
+
+    /// An error which isn't about a particular path.
+    pub fn other(kind: io::ErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            action: ErrorAction::Other,
+            path: None,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.action {
+            ErrorAction::ReadDirectory => "Error reading directory",
+            ErrorAction::ReadMetadata => "Error reading metadata for",
+            ErrorAction::ReadArchive => "Error reading archive",
+            ErrorAction::Other => return f.write_str(&self.message),
+        };
+        match &self.path {
+            Some(path) => write!(f, "{} {}: {}", action, path.display(), self.message),
+            None => write!(f, "{}: {}", action, self.message),
+        }
+    }
+}
+
+/// What a scan found since it last reported progress.
+///
+/// The counters are totals for the whole scan so far, the rest only covers the new findings.
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    pub scanned: u64,
+    pub files: u64,
+    pub directories: u64,
+    /// How many directories have been read completely.
+    pub directories_read: u64,
+    /// How many directories were found but not read yet.
+    pub directories_pending: u64,
+    /// How many levels below the scanned folders the deepest directory read so far is.
+    pub deepest: usize,
+    /// The directory a worker started reading most recently.
+    pub current_directory: Option<String>,
+    /// The longest path over the limit found so far.
+    pub longest: Option<OverLimit>,
+    pub over_limit: Vec<OverLimit>,
+    pub lengths: LengthHistogram,
+    pub case_collisions: Vec<CaseCollision>,
+}
+
+impl ScanProgress {
+    /// Adds new paths over the limit, keeping track of the longest one.
+    fn extend_over_limit(&mut self, paths: impl IntoIterator<Item = OverLimit>) {
+        for path in paths {
+            if self
+                .longest
+                .as_ref()
+                .is_none_or(|longest| path.size > longest.size)
+            {
+                self.longest = Some(path.clone());
+            }
+            self.over_limit.push(path);
+        }
+    }
+
+    /// Takes the new findings, leaving the totals in place.
+    fn take(&mut self) -> ScanProgress {
+        ScanProgress {
+            over_limit: mem::take(&mut self.over_limit),
+            lengths: mem::take(&mut self.lengths),
+            case_collisions: mem::take(&mut self.case_collisions),
+            current_directory: self.current_directory.clone(),
+            longest: self.longest.clone(),
+            ..*self
+        }
+    }
+}
+
+/// Scans `roots` and reports what it finds through `on_event`.
+///
+/// Errors are reported right away, progress every [`ScanOptions::update_interval`] or once
+/// [`ScanOptions::batch_size`] new paths over the limit were found. While `paused` is `true` no new
+/// directories are read. The scan stops early once `token` is cancelled, but the last progress is
+/// still reported.
+pub async fn scan(
+    roots: Vec<PathBuf>,
+    options: ScanOptions,
+    token: &CancellationToken,
+    mut paused: Option<watch::Receiver<bool>>,
+    mut on_event: impl AsyncFnMut(ScanEvent),
+) {
+    let mut options = options;
+    for invalid in options.load_ignore_files(&roots).await {
+        on_event(ScanEvent::Error(ScanError::other(
+            io::ErrorKind::InvalidInput,
+            invalid,
+        )))
+        .await;
+    }
+    let options = Arc::new(options);
+    // Only directories are tracked, and only when following links
+    let mut visited = HashSet::with_capacity(match options.expected {
+        Some(expected) if options.follow_symlinks => expected.directories as usize,
+        _ => 0,
+    });
+    let mut pending = Vec::new();
+    let mut progress = ScanProgress::default();
+    // Reversed, so the first root is scanned first
+    for root in roots.into_iter().rev() {
+        // A long folder name is often what pushes everything inside it over the limit
+        if options.include_roots
+            && options.is_reported(&root, true)
+            && options.is_modified_in_range(fs::metadata(&root).await.ok().as_ref())
+        {
+            let path_length = options.measure(&root);
+            progress.lengths.record(path_length);
+            if path_length > options.limit {
+                let path = strip_verbatim_prefix(&root).to_string_lossy().to_string();
+                progress.extend_over_limit([OverLimit {
+                    root: path.as_str().into(),
+                    path,
+                    size: path_length as u64,
+                    file_bytes: 0,
+                    is_dir: true,
+                    archive: None,
+                }]);
+            }
+        }
+
+        let visit_key = options.visit_key(&root).await;
+        visited.extend(visit_key.clone());
+        pending.push(Directory {
+            root: strip_verbatim_prefix(&root).to_string_lossy().into(),
+            path: root,
+            depth: 0,
+            visit_key,
+        });
+    }
+    // `tokio::fs` runs every call on the blocking thread pool, so the directories in here are
+    // read in parallel even though this one task polls them all and collects the results
+    let mut running = FuturesUnordered::new();
+
+    let mut last_update = Instant::now();
+
+    token
+        .run_until_cancelled(async {
+            loop {
+                // Waits right here while paused, so resuming continues where it left off
+                if let Some(paused) = &mut paused {
+                    let _ = paused.wait_for(|paused| !paused).await;
+                }
+
+                while running.len() < options.workers {
+                    let Some(directory) = pending.pop() else {
+                        break;
+                    };
+                    progress.current_directory = Some(
+                        strip_verbatim_prefix(&directory.path)
+                            .to_string_lossy()
+                            .to_string(),
+                    );
+                    running.push(scan_directory(directory, options.clone()));
+                }
+
+                // Nothing is running and nothing is left to start, so the scan is complete
+                let Some(directory) = running.next().await else {
+                    break;
+                };
+
+                progress.directories_read += 1;
+                progress.deepest = progress.deepest.max(directory.depth);
+                progress.scanned += directory.files + directory.directories;
+                progress.files += directory.files;
+                progress.directories += directory.directories;
+                progress.extend_over_limit(directory.over_limit);
+                progress.lengths.merge(&directory.lengths);
+                progress.case_collisions.extend(directory.case_collisions);
+                for subdirectory in directory.subdirectories {
+                    if let Some(key) = &subdirectory.visit_key
+                        && !visited.insert(key.clone())
+                    {
+                        continue;
+                    }
+                    pending.push(subdirectory);
+                }
+
+                for error in directory.errors {
+                    on_event(ScanEvent::Error(error)).await;
+                }
+
+                let now = Instant::now();
+                if now - last_update > options.update_interval
+                    || progress.over_limit.len() >= options.batch_size
+                {
+                    progress.directories_pending = (pending.len() + running.len()) as u64;
+                    on_event(ScanEvent::Progress(progress.take())).await;
+                    last_update = now;
+                }
+            }
+        })
+        .await;
+
+    // Reported outside of the cancelled block, so an aborted scan still delivers its last batch
+    progress.directories_pending = 0;
+    on_event(ScanEvent::Progress(progress)).await;
+}
+
+/// What [`run_scan`] scans and how.
+#[derive(Debug, Clone)]
+pub struct ScanConfig {
+    pub roots: Vec<PathBuf>,
+    pub options: ScanOptions,
+}
+
+/// Everything a completed scan found.
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    pub scanned: u64,
+    pub files: u64,
+    pub directories: u64,
+    /// How many levels below the scanned folders the deepest directory is.
+    pub deepest: usize,
+    pub over_limit: Vec<OverLimit>,
+    pub case_collisions: Vec<CaseCollision>,
+    /// The directories and entries which couldn't be read and were skipped.
+    pub errors: Vec<ScanError>,
+}
+
+/// Runs a scan to completion and collects everything it found.
+///
+/// Fails if one of the folders to scan isn't a readable folder. Errors while scanning don't
+/// stop it, they end up in [`ScanReport::errors`].
+///
+/// ```no_run
+/// use path_length_checker::{
+///     measure::LengthMetric,
+///     scanner::{ScanConfig, ScanOptions, run_scan},
+/// };
+///
+/// # async fn example() -> Result<(), path_length_checker::scanner::ScanError> {
+/// let report = run_scan(ScanConfig {
+///     roots: vec!["C:\\Data".into()],
+///     options: ScanOptions::new(240, LengthMetric::Utf16),
+/// })
+/// .await?;
+/// for path in &report.over_limit {
+///     println!("{} ({})", path.path, path.size);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn run_scan(config: ScanConfig) -> Result<ScanReport, ScanError> {
+    let ScanConfig { roots, options } = config;
+    for root in &roots {
+        match fs::metadata(root).await {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => {
+                return Err(ScanError::io(
+                    ErrorAction::ReadDirectory,
+                    root.clone(),
+                    &io::Error::from(io::ErrorKind::NotADirectory),
+                    &options,
+                ));
+            }
+            Err(err) => {
+                return Err(ScanError::io(
+                    ErrorAction::ReadDirectory,
+                    root.clone(),
+                    &err,
+                    &options,
+                ));
+            }
+        }
+    }
+
+    let mut report = ScanReport::default();
+    scan(
+        roots,
+        options,
+        &CancellationToken::new(),
+        None,
+        async |event| match event {
+            ScanEvent::Progress(progress) => {
+                report.scanned = progress.scanned;
+                report.files = progress.files;
+                report.directories = progress.directories;
+                report.deepest = report.deepest.max(progress.deepest);
+                report.over_limit.extend(progress.over_limit);
+                report.case_collisions.extend(progress.case_collisions);
+            }
+            ScanEvent::Error(error) => report.errors.push(error),
+        },
+    )
+    .await;
+    Ok(report)
+}
+
+/// Runs [`scan`] and yields its events as a stream.
+///
+/// The stream ends once the scan is complete, or once it's cancelled and the last progress was
+/// yielded.
+pub fn scan_stream(
+    roots: Vec<PathBuf>,
+    options: ScanOptions,
+    token: CancellationToken,
+    paused: Option<watch::Receiver<bool>>,
+) -> impl Stream<Item = ScanEvent> {
+    let (sender, receiver) = mpsc::channel(100);
+    let producer = async move {
+        let mut sender = sender;
+        scan(roots, options, &token, paused, async |event| {
+            // The receiver only goes away if nobody is interested in the scan anymore
+            let _ = sender.send(event).await;
+        })
+        .await;
+    };
+
+    // Polling both drives the scan while its events are taken out of the channel. The sender is
+    // dropped with the scan, which ends the receiver.
+    stream::select(
+        receiver,
+        producer.into_stream().filter_map(|()| async { None }),
+    )
+}
+
+/// How many files and directories a scan will visit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EntryCount {
+    pub files: u64,
+    pub directories: u64,
+}
+
+impl EntryCount {
+    pub fn total(self) -> u64 {
+        self.files + self.directories
+    }
+}
+
+/// Quickly counts the entries a scan with `options` will visit.
+///
+/// Path lengths aren't measured and only the directory listings are read, metadata just where
+/// the listing doesn't tell enough, so this runs well ahead of the scan. Hidden files are only
+/// recognized by name, so on Windows this can count a few more than the scan visits.
+pub async fn count_entries(roots: Vec<PathBuf>, options: &ScanOptions) -> EntryCount {
+    // The scan reports the invalid lines
+    let mut options = options.clone();
+    options.load_ignore_files(&roots).await;
+    let mut visited = HashSet::new();
+    let mut stack = Vec::new();
+    for root in roots {
+        visited.extend(options.visit_key(&root).await);
+        stack.push((root, 0));
+    }
+    let mut count = EntryCount::default();
+
+    while let Some((path, depth)) = stack.pop() {
+        let Ok(mut entries) = fs::read_dir(&path).await else {
+            continue;
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let entry_path = entry.path();
+            let Ok(file_type) = entry.file_type().await else {
+                continue;
+            };
+            // Where a link points only shows in the metadata of its target
+            let is_dir = if file_type.is_symlink() && options.follow_symlinks {
+                fs::metadata(&entry_path)
+                    .await
+                    .is_ok_and(|metadata| metadata.is_dir())
+            } else {
+                file_type.is_dir()
+            };
+
+            if options.is_excluded(&entry_path, is_dir) || options.is_hidden(&entry_path, None) {
+                continue;
+            }
+
+            if is_dir {
+                count.directories += 1;
+            } else {
+                count.files += 1;
+            }
+
+            if is_dir && options.descends_into(depth) {
+                if let Some(key) = options.visit_key(&entry_path).await
+                    && !visited.insert(key)
+                {
+                    continue;
+                }
+                stack.push((entry_path, depth + 1));
+            }
+        }
+    }
+
+    count
+}
+
+/// What was found in a single directory.
+#[derive(Default)]
+struct DirectoryScan {
+    /// How many levels below the scanned folder the directory is.
+    depth: usize,
+    files: u64,
+    /// Symbolic links to directories only count as directories when they are followed.
+    directories: u64,
+    over_limit: Vec<OverLimit>,
+    lengths: LengthHistogram,
+    case_collisions: Vec<CaseCollision>,
+    /// Subdirectories to scan next.
+    subdirectories: Vec<Directory>,
+    errors: Vec<ScanError>,
+}
+
+/// Measures the entries of the zip archive at `path` like the entries of a directory.
+#[cfg(feature = "zip")]
+async fn scan_archive(
+    path: &Path,
+    options: &ScanOptions,
+    root: &Arc<str>,
+    result: &mut DirectoryScan,
+) {
+    let entries = {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || archive::extracted_entries(&path))
+            .await
+            .unwrap_or_else(|err| Err(io::Error::other(err)))
+    };
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(err) => {
+            result.errors.push(ScanError::io(
+                ErrorAction::ReadArchive,
+                path.to_path_buf(),
+                &err,
+                options,
+            ));
+            return;
+        }
+    };
+
+    let archive: Arc<str> = strip_verbatim_prefix(path).to_string_lossy().into();
+    for entry in entries {
+        if !options.is_reported(&entry.path, entry.is_dir) {
+            continue;
+        }
+        let path_length = options.measure(&entry.path);
+        result.lengths.record(path_length);
+
+        if path_length > options.limit {
+            result.over_limit.push(OverLimit {
+                path: strip_verbatim_prefix(&entry.path)
+                    .to_string_lossy()
+                    .to_string(),
+                size: path_length as u64,
+                file_bytes: entry.size,
+                is_dir: entry.is_dir,
+                root: root.clone(),
+                archive: Some(archive.clone()),
+            });
+        }
+    }
+}
+
+/// A directory waiting to be scanned.
+struct Directory {
+    path: PathBuf,
+    depth: usize,
+    /// The scanned folder this directory is in.
+    root: Arc<str>,
+    /// See [`ScanOptions::visit_key`].
+    visit_key: Option<PathBuf>,
+}
+
+/// Reads the entries of a single directory.
+///
+/// The scan runs several of these at once, one per worker.
+async fn scan_directory(directory: Directory, options: Arc<ScanOptions>) -> DirectoryScan {
+    let Directory {
+        path, depth, root, ..
+    } = directory;
+    let mut result = DirectoryScan {
+        depth,
+        ..Default::default()
+    };
+
+    let mut entries = match options.retry(async || fs::read_dir(&path).await).await {
+        Ok(entries) => entries,
+        Err(err) => {
+            // The directory itself was already measured when its parent was read
+            result.errors.push(ScanError::io(
+                ErrorAction::ReadDirectory,
+                path,
+                &err,
+                &options,
+            ));
+            return result;
+        }
+    };
+
+    // Collisions can only happen between entries of the same directory, so this stays small
+    let mut names: HashMap<String, Vec<String>> = HashMap::new();
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let entry_path = entry.path();
+        let metadata = options.retry(async || options.metadata(&entry).await).await;
+        let is_dir = metadata.as_ref().is_ok_and(|metadata| metadata.is_dir());
+
+        if options.is_excluded(&entry_path, is_dir)
+            || options.is_hidden(&entry_path, metadata.as_ref().ok())
+        {
+            continue;
+        }
+
+        if options.detect_case_collisions {
+            names
+                .entry(entry.file_name().to_string_lossy().to_lowercase())
+                .or_default()
+                .push(
+                    strip_verbatim_prefix(&entry_path)
+                        .to_string_lossy()
+                        .to_string(),
+                );
+        }
+
+        if options.is_reported(&entry_path, is_dir)
+            && options.is_modified_in_range(metadata.as_ref().ok())
+        {
+            let path_length = options.measure(&entry_path);
+            result.lengths.record(path_length);
+
+            if path_length > options.limit {
+                result.over_limit.push(OverLimit {
+                    path: strip_verbatim_prefix(&entry_path)
+                        .to_string_lossy()
+                        .to_string(),
+                    size: path_length as u64,
+                    file_bytes: metadata
+                        .as_ref()
+                        .ok()
+                        .filter(|metadata| !metadata.is_dir())
+                        .map_or(0, |metadata| metadata.len()),
+                    is_dir,
+                    root: root.clone(),
+                    archive: None,
+                });
+            }
+        }
+
+        #[cfg(feature = "zip")]
+        if options.scan_archives
+            && !is_dir
+            && archive::is_zip(&entry_path)
+            && options.is_modified_in_range(metadata.as_ref().ok())
+        {
+            scan_archive(&entry_path, &options, &root, &mut result).await;
+        }
+
+        match metadata {
+            Ok(_) => {
+                if is_dir && options.descends_into(depth) {
+                    result.subdirectories.push(Directory {
+                        visit_key: options.visit_key(&entry_path).await,
+                        path: entry_path,
+                        depth: depth + 1,
+                        root: root.clone(),
+                    });
+                }
+            }
+            Err(err) => {
+                result.errors.push(ScanError::io(
+                    ErrorAction::ReadMetadata,
+                    entry_path,
+                    &err,
+                    &options,
+                ));
+            }
+        }
+
+        if is_dir {
+            result.directories += 1;
+        } else {
+            result.files += 1;
+        }
+    }
+
+    for mut paths in names.into_values() {
+        if paths.len() > 1 {
+            paths.sort();
+            result.case_collisions.push(CaseCollision { paths });
+        }
+    }
+
+    result
+}
+
+/// Whether trying again might get past `err`.
+///
+/// A missing path or denied access won't change by waiting, so those are reported right away.
+fn is_transient(err: &io::Error) -> bool {
+    !matches!(
+        err.kind(),
+        io::ErrorKind::NotFound
+            | io::ErrorKind::PermissionDenied
+            | io::ErrorKind::NotADirectory
+            | io::ErrorKind::InvalidFilename
+            | io::ErrorKind::InvalidInput
+    )
+}
+
+/// Windows can't open paths this long, in UTF-16 code units, unless long paths are enabled.
+const WINDOWS_MAX_PATH: usize = 260;
+
+/// Whether `err` is likely caused by `path` being too long for the system to open.
+///
+/// Without long path support, Windows reports such a path as not found, even though its parent
+/// folder just listed it.
+fn is_too_long(err: &io::Error, path: &Path) -> bool {
+    match err.kind() {
+        io::ErrorKind::InvalidFilename => true,
+        io::ErrorKind::NotFound => {
+            cfg!(windows) && LengthMetric::Utf16.measure(path) >= WINDOWS_MAX_PATH
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeSet,
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    use iced::futures::StreamExt;
+    use tempfile::TempDir;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{
+        ErrorAction, ScanConfig, ScanError, ScanEvent, ScanOptions, normalize_extension, run_scan,
+        scan, scan_stream,
+    };
+    use crate::measure::LengthMetric;
+
+    /// Creates a temporary folder with `files` in it, along with the folders they are in.
+    fn tree(files: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        dir
+    }
+
+    /// Options which report every path longer than `root` plus `extra` bytes.
+    fn options(root: &Path, extra: usize) -> ScanOptions {
+        ScanOptions::new(root.as_os_str().len() + extra, LengthMetric::Bytes)
+    }
+
+    /// Everything a scan reported, with the paths relative to the scanned folder.
+    struct Scanned {
+        scanned: u64,
+        over_limit: Vec<(String, u64)>,
+        errors: Vec<ScanError>,
+    }
+
+    impl Scanned {
+        fn paths(&self) -> BTreeSet<&str> {
+            self.over_limit
+                .iter()
+                .map(|(path, _)| path.as_str())
+                .collect()
+        }
+    }
+
+    async fn scan_tree(root: &Path, options: ScanOptions) -> Scanned {
+        let mut scanned = Scanned {
+            scanned: 0,
+            over_limit: Vec::new(),
+            errors: Vec::new(),
+        };
+        scan(
+            vec![root.to_path_buf()],
+            options,
+            &CancellationToken::new(),
+            None,
+            async |event| match event {
+                ScanEvent::Progress(progress) => {
+                    scanned.scanned = progress.scanned;
+                    scanned
+                        .over_limit
+                        .extend(progress.over_limit.into_iter().map(|path| {
+                            let relative = Path::new(&path.path).strip_prefix(root).unwrap();
+                            (relative.to_string_lossy().replace('\\', "/"), path.size)
+                        }));
+                }
+                ScanEvent::Error(error) => scanned.errors.push(error),
+            },
+        )
+        .await;
+        scanned
+    }
+
+    const FILES: &[&str] = &[
+        "short.txt",
+        "folder/a_much_longer_file_name.txt",
+        "folder/x.txt",
+    ];
+
+    #[tokio::test]
+    async fn reports_paths_over_the_limit() {
+        let dir = tree(FILES);
+        let base = dir.path().as_os_str().len() as u64;
+
+        let scanned = scan_tree(dir.path(), options(dir.path(), 20)).await;
+
+        assert_eq!(
+            scanned.over_limit,
+            vec![("folder/a_much_longer_file_name.txt".to_string(), base + 35)]
+        );
+        assert_eq!(scanned.scanned, 4);
+        assert!(scanned.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_nothing_below_the_limit() {
+        let dir = tree(FILES);
+
+        let scanned = scan_tree(dir.path(), options(dir.path(), 1000)).await;
+
+        assert!(scanned.over_limit.is_empty());
+        assert_eq!(scanned.scanned, 4);
+        assert!(scanned.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_everything_with_a_limit_of_zero() {
+        let dir = tree(FILES);
+
+        let scanned = scan_tree(dir.path(), ScanOptions::new(0, LengthMetric::Bytes)).await;
+
+        assert_eq!(
+            scanned.paths(),
+            BTreeSet::from([
+                "folder",
+                "folder/a_much_longer_file_name.txt",
+                "folder/x.txt",
+                "short.txt",
+            ])
+        );
+        assert_eq!(scanned.over_limit.len(), 4);
+        assert_eq!(scanned.scanned, 4);
+    }
+
+    #[tokio::test]
+    async fn scan_stream_ends_after_the_final_progress() {
+        let dir = tree(FILES);
+
+        let events: Vec<ScanEvent> = scan_stream(
+            vec![dir.path().to_path_buf()],
+            ScanOptions::new(0, LengthMetric::Bytes),
+            CancellationToken::new(),
+            None,
+        )
+        .collect()
+        .await;
+
+        let over_limit: usize = events
+            .iter()
+            .map(|event| match event {
+                ScanEvent::Progress(progress) => progress.over_limit.len(),
+                ScanEvent::Error(error) => panic!("unexpected error: {}", error),
+            })
+            .sum();
+        assert_eq!(over_limit, 4);
+        let Some(ScanEvent::Progress(progress)) = events.last() else {
+            panic!("the last event should be the final progress");
+        };
+        assert_eq!(progress.scanned, 4);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reports_unreadable_directories() {
+        use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+        let dir = tree(&["locked/inner.txt", "open.txt"]);
+        let locked = dir.path().join("locked");
+        std::fs::set_permissions(&locked, Permissions::from_mode(0o000)).unwrap();
+        // Permissions don't keep root out, so there is nothing to test then
+        if std::fs::read_dir(&locked).is_ok() {
+            std::fs::set_permissions(&locked, Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let scanned = scan_tree(dir.path(), ScanOptions::new(0, LengthMetric::Bytes)).await;
+        std::fs::set_permissions(&locked, Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(scanned.errors.len(), 1);
+        let error = &scanned.errors[0];
+        assert_eq!(error.action, ErrorAction::ReadDirectory);
+        assert_eq!(error.kind, std::io::ErrorKind::PermissionDenied);
+        assert_eq!(error.path.as_deref(), Some(locked.as_path()));
+        // The folder itself is still measured, only its entries are missing
+        assert_eq!(scanned.paths(), BTreeSet::from(["locked", "open.txt"]));
+        assert_eq!(scanned.scanned, 2);
+    }
+
+    #[tokio::test]
+    async fn reports_only_files_with_the_extensions() {
+        let dir = tree(&["photo.PNG", "notes.txt", "folder/deep.png"]);
+
+        let scanned = scan_tree(
+            dir.path(),
+            ScanOptions {
+                extensions: vec![normalize_extension("*.png")],
+                ..ScanOptions::new(0, LengthMetric::Bytes)
+            },
+        )
+        .await;
+
+        assert_eq!(
+            scanned.paths(),
+            BTreeSet::from(["folder/deep.png", "photo.PNG"])
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_maximum_depth() {
+        let dir = tree(&["a.txt", "one/b.txt", "one/two/c.txt"]);
+
+        let top = scan_tree(
+            dir.path(),
+            ScanOptions {
+                max_depth: Some(0),
+                ..ScanOptions::new(0, LengthMetric::Bytes)
+            },
+        )
+        .await;
+        assert_eq!(top.paths(), BTreeSet::from(["a.txt", "one"]));
+
+        let deeper = scan_tree(
+            dir.path(),
+            ScanOptions {
+                max_depth: Some(1),
+                ..ScanOptions::new(0, LengthMetric::Bytes)
+            },
+        )
+        .await;
+        assert_eq!(
+            deeper.paths(),
+            BTreeSet::from(["a.txt", "one", "one/b.txt", "one/two"])
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlink_loops_are_reported_once() {
+        let dir = tree(&["folder/file.txt"]);
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("folder/loop")).unwrap();
+
+        for follow_symlinks in [false, true] {
+            let options = ScanOptions {
+                follow_symlinks,
+                ..ScanOptions::new(0, LengthMetric::Bytes)
+            };
+            let scanned =
+                tokio::time::timeout(Duration::from_secs(10), scan_tree(dir.path(), options))
+                    .await
+                    .expect("the scan should finish");
+
+            assert_eq!(
+                scanned.paths(),
+                BTreeSet::from(["folder", "folder/file.txt", "folder/loop"]),
+                "following links: {}",
+                follow_symlinks
+            );
+            assert_eq!(
+                scanned.over_limit.len(),
+                3,
+                "following links: {}",
+                follow_symlinks
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn delivers_pending_paths_when_cancelled() {
+        use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+        let dir = tree(&["a.txt", "b.txt", "locked/c.txt"]);
+        let locked = dir.path().join("locked");
+        std::fs::set_permissions(&locked, Permissions::from_mode(0o000)).unwrap();
+        // Permissions don't keep root out, so there is nothing to test then
+        if std::fs::read_dir(&locked).is_ok() {
+            std::fs::set_permissions(&locked, Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        // The locked folder is read last, so its error arrives before the 100ms throttle fires
+        let token = CancellationToken::new();
+        let mut events = Vec::new();
+        scan(
+            vec![dir.path().to_path_buf()],
+            ScanOptions::new(0, LengthMetric::Bytes),
+            &token,
+            None,
+            async |event| {
+                if matches!(event, ScanEvent::Error(_)) {
+                    token.cancel();
+                }
+                events.push(event);
+            },
+        )
+        .await;
+        std::fs::set_permissions(&locked, Permissions::from_mode(0o755)).unwrap();
+
+        assert!(token.is_cancelled());
+        let Some(ScanEvent::Progress(progress)) = events.last() else {
+            panic!("the last event should be the final progress");
+        };
+        assert_eq!(progress.scanned, 3);
+        let mut paths: Vec<_> = progress
+            .over_limit
+            .iter()
+            .map(|path| Path::new(&path.path).strip_prefix(dir.path()).unwrap())
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [Path::new("a.txt"), Path::new("b.txt"), Path::new("locked")]
+        );
+    }
+
+    #[test]
+    fn too_long_errors_use_the_scanned_length() {
+        // "é" decomposed, which is 3 bytes instead of 2 once composed
+        let path = PathBuf::from("/data/cafe\u{301}");
+        let options = ScanOptions {
+            normalize_unicode: true,
+            ..ScanOptions::new(0, LengthMetric::Bytes)
+        };
+        let err = std::io::Error::from(std::io::ErrorKind::InvalidFilename);
+
+        let error = ScanError::io(ErrorAction::ReadDirectory, path, &err, &options);
+
+        assert!(
+            error.message.contains("the path is 11 Bytes long"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[tokio::test]
+    async fn run_scan_collects_the_report() {
+        let dir = tree(FILES);
+        let long = dir.path().join("folder/a_much_longer_file_name.txt");
+
+        let report = run_scan(ScanConfig {
+            roots: vec![dir.path().to_path_buf()],
+            options: options(dir.path(), 20),
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.scanned, 4);
+        assert_eq!(report.files, 3);
+        assert_eq!(report.directories, 1);
+        assert_eq!(report.deepest, 1);
+        assert_eq!(report.over_limit.len(), 1);
+        assert_eq!(Path::new(&report.over_limit[0].path), long);
+        assert_eq!(report.over_limit[0].size, long.as_os_str().len() as u64);
+        assert!(report.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_scan_rejects_a_file_as_the_root() {
+        let dir = tree(&["file.txt"]);
+        let file = dir.path().join("file.txt");
+
+        let error = run_scan(ScanConfig {
+            roots: vec![file.clone()],
+            options: ScanOptions::new(0, LengthMetric::Bytes),
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(error.kind, std::io::ErrorKind::NotADirectory);
+        assert_eq!(error.action, ErrorAction::ReadDirectory);
+        assert_eq!(error.path, Some(file));
+    }
+}
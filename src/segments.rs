@@ -0,0 +1,75 @@
+use std::{
+    collections::HashMap,
+    path::{Component, Path},
+};
+
+use crate::{measure::LengthMetric, scanner::OverLimit};
+
+/// How often each folder name appears in the paths over the limit.
+///
+/// A single badly named folder deep in a tree pushes every path below it over the limit, so the
+/// names contributing the most length across all results are usually the best ones to shorten.
+#[derive(Debug, Clone, Default)]
+pub struct SegmentTally {
+    counts: HashMap<String, u64>,
+}
+
+/// A folder name which appears in many paths over the limit.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub name: String,
+    pub length: usize,
+    /// How many paths over the limit contain it.
+    pub count: u64,
+}
+
+impl SegmentTally {
+    /// How many names the summary and reports list.
+    pub const DEFAULT_COUNT: usize = 10;
+
+    /// Counts the folder names of `path` below its scanned folder.
+    ///
+    /// The scanned folder itself is part of every path, and file names are nearly always unique,
+    /// so neither would tell anything.
+    pub fn record(&mut self, path: &OverLimit) {
+        let relative = path.relative_path();
+        let relative = Path::new(&*relative);
+        let folders = if path.is_dir {
+            Some(relative)
+        } else {
+            relative.parent()
+        };
+        for component in folders.into_iter().flat_map(Path::components) {
+            if let Component::Normal(name) = component {
+                *self
+                    .counts
+                    .entry(name.to_string_lossy().to_string())
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+
+    /// Returns up to `count` names contributing the most length across all paths, the most first.
+    pub fn top(&self, count: usize, metric: LengthMetric) -> Vec<Segment> {
+        let mut segments: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(name, count)| Segment {
+                length: metric.measure(Path::new(name)),
+                name: name.clone(),
+                count: *count,
+            })
+            .collect();
+        segments.sort_by(|a, b| {
+            (b.length as u64 * b.count)
+                .cmp(&(a.length as u64 * a.count))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        segments.truncate(count);
+        segments
+    }
+}
@@ -0,0 +1,75 @@
+use std::net::{Ipv4Addr, SocketAddr};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Requests with a longer head than this are rejected, nothing this serves needs more.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Serves `report` as JSON on `localhost:port` until the process gets Ctrl+C.
+///
+/// Every `GET` request gets the report, no matter the path, so other tools can simply poll it.
+pub async fn serve(port: u16, report: String) -> Result<(), String> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = TcpListener::bind(address)
+        .await
+        .map_err(|err| format!("Failed to listen on {}: {}", address, err))?;
+    eprintln!("Serving the results on http://{}", address);
+
+    // Created once, so a Ctrl+C while a connection is being accepted isn't missed
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let report = report.clone();
+                    tokio::spawn(async move {
+                        // The client going away mid-request is no reason to stop serving
+                        let _ = respond(stream, &report).await;
+                    });
+                }
+                Err(err) => eprintln!("Failed to accept a connection: {}", err),
+            },
+            _ = &mut ctrl_c => {
+                eprintln!("Stopped serving the results");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Answers a single HTTP request on `stream`.
+async fn respond(mut stream: TcpStream, report: &str) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|end| end == b"\r\n\r\n") {
+        if request.len() > MAX_REQUEST_SIZE {
+            return write_response(&mut stream, "431 Request Header Fields Too Large", "").await;
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    if request.starts_with(b"GET ") {
+        write_response(&mut stream, "200 OK", report).await
+    } else {
+        write_response(&mut stream, "405 Method Not Allowed", "").await
+    }
+}
+
+async fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,172 @@
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+use iced::{Point, Size, window};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::measure::LengthMetric;
+
+/// Choices which are remembered across launches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub last_folder: Option<PathBuf>,
+    /// The folders scanned last, the most recent first.
+    pub recent_folders: Vec<PathBuf>,
+    pub limit: usize,
+    pub metric: LengthMetric,
+    /// `None` until the app was closed once.
+    pub window: Option<WindowState>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            last_folder: None,
+            recent_folders: Vec::new(),
+            limit: 240,
+            metric: LengthMetric::default(),
+            window: None,
+        }
+    }
+}
+
+/// The size and position of the window, in logical pixels.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowState {
+    pub width: f32,
+    pub height: f32,
+    /// Not every platform tells where the window is, Wayland for example doesn't.
+    pub position: Option<(f32, f32)>,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        let size = window::Settings::default().size;
+        Self {
+            width: size.width,
+            height: size.height,
+            position: None,
+        }
+    }
+}
+
+impl WindowState {
+    /// Windows smaller than this are left at the default size, they would barely be usable.
+    const MIN_SIZE: f32 = 200.0;
+
+    /// The settings to open the window as it was.
+    ///
+    /// The window is moved onto the closest screen if it would be off-screen, for example
+    /// because the monitor it was on got unplugged.
+    pub fn window_settings(self) -> window::Settings {
+        let default = window::Settings::default();
+        let size = if self.width >= Self::MIN_SIZE && self.height >= Self::MIN_SIZE {
+            Size::new(self.width, self.height)
+        } else {
+            default.size
+        };
+        let position = match self.position {
+            Some(position) => {
+                let (x, y) = visible_position(size, position);
+                window::Position::Specific(Point::new(x, y))
+            }
+            None => default.position,
+        };
+        window::Settings {
+            size,
+            position,
+            ..default
+        }
+    }
+}
+
+/// Moves a window of `size` at `position` into the work area of the closest monitor.
+///
+/// The whole window is kept on the monitor if it fits, otherwise at least its top left corner.
+#[cfg(windows)]
+fn visible_position(size: Size, (x, y): (f32, f32)) -> (f32, f32) {
+    use windows_sys::Win32::{
+        Foundation::RECT,
+        Graphics::Gdi::{GetMonitorInfoW, MONITOR_DEFAULTTONEAREST, MONITORINFO, MonitorFromRect},
+        UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI},
+    };
+
+    // Monitors are in physical pixels, but logical ones are close enough to find the nearest
+    let rect = RECT {
+        left: x as i32,
+        top: y as i32,
+        right: (x + size.width) as i32,
+        bottom: (y + size.height) as i32,
+    };
+    // SAFETY: all pointers point to values which live until the calls return
+    let (work, scale) = unsafe {
+        let monitor = MonitorFromRect(&rect, MONITOR_DEFAULTTONEAREST);
+        let mut info: MONITORINFO = std::mem::zeroed();
+        info.cbSize = size_of::<MONITORINFO>() as u32;
+        if GetMonitorInfoW(monitor, &mut info) == 0 {
+            return (x, y);
+        }
+        let (mut dpi_x, mut dpi_y) = (96, 96);
+        // Without the DPI the monitor is treated as unscaled
+        GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+        (info.rcWork, dpi_x.max(1) as f32 / 96.0)
+    };
+
+    let (left, top) = (work.left as f32 / scale, work.top as f32 / scale);
+    let (right, bottom) = (work.right as f32 / scale, work.bottom as f32 / scale);
+    (
+        x.min(right - size.width).max(left),
+        y.min(bottom - size.height).max(top),
+    )
+}
+
+/// Other platforms' window managers keep new windows on screen themselves.
+#[cfg(not(windows))]
+fn visible_position(_size: Size, position: (f32, f32)) -> (f32, f32) {
+    position
+}
+
+/// How many folders [`Settings::recent_folders`] keeps.
+const MAX_RECENT_FOLDERS: usize = 10;
+
+/// Moves `folder` to the top of `recent`, forgetting the oldest ones beyond the cap.
+pub fn remember_folder(recent: &mut Vec<PathBuf>, folder: &Path) {
+    recent.retain(|existing| existing != folder);
+    recent.insert(0, folder.to_path_buf());
+    recent.truncate(MAX_RECENT_FOLDERS);
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        ProjectDirs::from("de", "Rahn-IT", "path-length-checker")
+            .map(|dirs| dirs.config_dir().join("settings.json"))
+    }
+
+    /// Loads the saved settings.
+    ///
+    /// Falls back to the defaults if the file is missing or can't be parsed.
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub async fn save(self) -> Result<(), String> {
+        let path = Self::path().ok_or("No configuration directory available")?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(&self)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        fs::write(&path, content)
+            .await
+            .map_err(|e| format!("Failed to save settings: {}", e))
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::measure::LengthMetric;
+
+/// Returns the length of the file name without its extension and the length of the extension,
+/// including the dot.
+pub fn name_lengths(path: &Path, metric: LengthMetric) -> (usize, usize) {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let (stem, extension) = split_name(&name);
+    (
+        stem.chars().map(|c| metric.char_length(c)).sum(),
+        extension.chars().map(|c| metric.char_length(c)).sum(),
+    )
+}
+
+/// Splits a file name into its stem and extension, keeping the dot on the extension.
+///
+/// A leading dot, as in `.gitignore`, belongs to the stem.
+fn split_name(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(index) if index > 0 => name.split_at(index),
+        _ => (name, ""),
+    }
+}
+
+/// Suggests a shortened file name that brings `path` within `limit`.
+///
+/// The extension is kept and characters are trimmed from the end of the file stem.
+/// Returns `None` if the path already fits or if trimming the stem alone isn't enough.
+pub fn suggest_name(path: &Path, limit: usize, metric: LengthMetric) -> Option<String> {
+    let length = metric.measure(path);
+    if length <= limit {
+        return None;
+    }
+    let excess = length - limit;
+
+    let name = path.file_name()?.to_string_lossy();
+    let (stem, extension) = split_name(&name);
+
+    let mut removed = 0;
+    let mut end = stem.len();
+    for (index, c) in stem.char_indices().rev() {
+        if removed >= excess {
+            break;
+        }
+        removed += metric.char_length(c);
+        end = index;
+    }
+    if removed < excess {
+        return None;
+    }
+
+    // Windows doesn't allow names ending in a space or a dot
+    let trimmed = stem[..end].trim_end_matches([' ', '.']);
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}{}", trimmed, extension))
+}
+
+/// Folder names aren't shortened below this many characters, so they stay recognizable.
+const MIN_FOLDER_NAME: usize = 8;
+
+/// Suggests a shortened path that brings `path` within `limit` by shortening its folder names.
+///
+/// The longest folder name keeps losing its last character until the path fits, which evens out
+/// long names before touching shorter ones. The file name stays as it is. This is only meant as a
+/// starting point, it doesn't check whether the shortened folders collide with others.
+/// Returns `None` if the path already fits or if the folders can't be shortened enough.
+pub fn suggest_path(path: &Path, limit: usize, metric: LengthMetric) -> Option<String> {
+    let length = metric.measure(path);
+    if length <= limit {
+        return None;
+    }
+    let mut excess = length - limit;
+
+    let file_name = path.file_name()?;
+    let mut prefix = PathBuf::new();
+    let mut folders: Vec<Vec<char>> = Vec::new();
+    for component in path.parent()?.components() {
+        match component {
+            Component::Normal(name) => folders.push(name.to_string_lossy().chars().collect()),
+            other => prefix.push(other),
+        }
+    }
+
+    let folder_length =
+        |folder: &[char]| -> usize { folder.iter().map(|c| metric.char_length(*c)).sum() };
+    while excess > 0 {
+        let longest = folders
+            .iter_mut()
+            .filter(|folder| folder.len() > MIN_FOLDER_NAME)
+            .max_by_key(|folder| folder_length(folder.as_slice()))?;
+        let removed = longest.pop()?;
+        excess = excess.saturating_sub(metric.char_length(removed));
+    }
+
+    let mut suggested = prefix;
+    for folder in folders {
+        let name: String = folder.into_iter().collect();
+        // Windows doesn't allow names ending in a space or a dot
+        suggested.push(name.trim_end_matches([' ', '.']));
+    }
+    suggested.push(file_name);
+
+    Some(suggested.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{suggest_name, suggest_path};
+    use crate::measure::LengthMetric;
+
+    fn name(path: &str, limit: usize) -> Option<String> {
+        suggest_name(Path::new(path), limit, LengthMetric::Bytes)
+    }
+
+    fn path(path: &str, limit: usize) -> Option<String> {
+        suggest_path(Path::new(path), limit, LengthMetric::Bytes)
+    }
+
+    /// Compares paths by their components, as Windows writes the suggestions with backslashes.
+    fn assert_path(suggested: Option<String>, expected: &str) {
+        assert_eq!(
+            suggested.as_deref().map(Path::new),
+            Some(Path::new(expected))
+        );
+    }
+
+    #[test]
+    fn names_lose_the_end_of_the_stem() {
+        assert_eq!(
+            name("/data/report_final.txt", 18).as_deref(),
+            Some("report_f.txt")
+        );
+        // Windows doesn't allow a trailing space
+        assert_eq!(name("/data/notes 1.txt", 16).as_deref(), Some("notes.txt"));
+    }
+
+    #[test]
+    fn names_count_in_the_metric() {
+        // Each ü is 2 bytes, but a single character
+        let path = Path::new("/data/üüüü.txt");
+        assert_eq!(
+            suggest_name(path, 14, LengthMetric::Bytes).as_deref(),
+            Some("üü.txt")
+        );
+        assert_eq!(
+            suggest_name(path, 12, LengthMetric::Chars).as_deref(),
+            Some("üü.txt")
+        );
+    }
+
+    #[test]
+    fn no_name_when_nothing_can_be_shortened() {
+        // Already within the limit
+        assert_eq!(name("/data/report.txt", 100), None);
+        // Would need the whole stem, or more
+        assert_eq!(name("/data/ab.txt", 10), None);
+        assert_eq!(name("/data/ab.txt", 5), None);
+    }
+
+    #[test]
+    fn paths_lose_the_end_of_the_longest_folder() {
+        let long = "/data/projects_archive/customer_documents/file.txt";
+        // The longer folder is shortened until it's as long as the other one
+        assert_path(
+            path(long, 45),
+            "/data/projects_archiv/customer_docum/file.txt",
+        );
+        assert_path(path(long, 40), "/data/projects_arc/customer_doc/file.txt");
+        // Windows doesn't allow a trailing dot
+        assert_path(
+            path("/data/folder one.x/file.txt", 26),
+            "/data/folder one/file.txt",
+        );
+    }
+
+    #[test]
+    fn paths_keep_the_minimum_folder_length() {
+        let long = "/data/projects_archive/customer_documents/file.txt";
+        // Folders aren't shortened below 8 characters, and the short one isn't touched at all
+        assert_path(path(long, 33), "/data/projects_/customer/file.txt");
+        assert_eq!(path(long, 30), None);
+    }
+
+    #[test]
+    fn no_path_when_nothing_can_be_shortened() {
+        assert_eq!(path("/data/projects/file.txt", 100), None);
+        // Only short folders, and the file name isn't changed
+        assert_eq!(path("/data/short/a_very_long_file_name.txt", 20), None);
+    }
+}
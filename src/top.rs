@@ -0,0 +1,101 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+use crate::scanner::OverLimit;
+
+/// The longest paths found so far.
+///
+/// Kept in a min-heap bounded to `capacity`, so adding a path only has to compare it against the
+/// shortest of the current top paths, no matter how many results there are.
+#[derive(Debug, Clone)]
+pub struct TopPaths {
+    heap: BinaryHeap<Reverse<Ranked>>,
+    capacity: usize,
+}
+
+impl TopPaths {
+    pub const DEFAULT_CAPACITY: usize = 20;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            heap: BinaryHeap::with_capacity(capacity + 1),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn push(&mut self, path: OverLimit) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.heap.len() == self.capacity
+            && self
+                .heap
+                .peek()
+                .is_some_and(|Reverse(shortest)| shortest.0.size >= path.size)
+        {
+            return;
+        }
+        self.heap.push(Reverse(Ranked(path)));
+        if self.heap.len() > self.capacity {
+            self.heap.pop();
+        }
+    }
+
+    /// Replaces the top paths with the longest of `paths`, keeping up to `capacity` of them.
+    pub fn rebuild<'a>(&mut self, paths: impl IntoIterator<Item = &'a OverLimit>, capacity: usize) {
+        *self = Self::new(capacity);
+        for path in paths {
+            self.push(path.clone());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    /// Returns the top paths, the longest first.
+    pub fn sorted(&self) -> Vec<&OverLimit> {
+        let mut paths: Vec<_> = self.heap.iter().map(|Reverse(ranked)| &ranked.0).collect();
+        paths.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        paths
+    }
+}
+
+impl Default for TopPaths {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+/// Orders paths by their length, so the heap can compare them.
+#[derive(Debug, Clone)]
+struct Ranked(OverLimit);
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .size
+            .cmp(&other.0.size)
+            .then_with(|| other.0.path.cmp(&self.0.path))
+    }
+}
@@ -1,47 +1,416 @@
-use std::{mem, ops::Not, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, io, mem,
+    ops::Not,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
-use iced::{Alignment::Center, Font, Length, Task, alignment::Vertical, task::sipper};
+use iced::{
+    Alignment::Center,
+    Event, Font, Length, Subscription, Task, Theme,
+    alignment::Vertical,
+    event,
+    futures::{StreamExt, stream},
+    keyboard, task, window,
+};
 use rfd::{AsyncFileDialog, FileHandle};
-use tokio::{fs, io::AsyncWriteExt, time::Instant};
+use tokio::{fs, sync::watch, time::Instant};
 use tokio_util::sync::CancellationToken;
 
+use crate::{
+    baseline::{Baseline, Comparison},
+    date,
+    disk::DiskSpace,
+    error_log::ErrorLog,
+    export::{self, CsvWriter, Delimiter, JsonPath, JsonReport, Separators},
+    histogram::LengthHistogram,
+    measure::{self, LengthMetric},
+    memory, project, reveal,
+    scanner::{
+        self, CaseCollision, EntryCount, OverLimit, ScanError, ScanEvent, ScanOptions,
+        ScanProgress, Severity,
+    },
+    segments::{Segment, SegmentTally},
+    settings::{self, Settings, WindowState},
+    suggest::{suggest_name, suggest_path},
+    top::TopPaths,
+};
+
 #[derive(Debug, Clone)]
 pub enum Message {
     SelectFolder,
     SelectedFolder(Option<Arc<FileHandle>>),
+    AddFolder,
+    CancelSelection,
+    PathInputChanged(String),
+    UsePath,
+    PathChecked(Result<PathBuf, String>),
+    FileDropped(PathBuf),
+    WindowMoved(iced::Point),
+    WindowResized(iced::Size),
+    WindowCloseRequested(window::Id),
+    RecentFolderSelected(RecentFolder),
+    AddedFolder(Option<Arc<FileHandle>>),
+    RemoveFolder(PathBuf),
     AbortScan,
+    PauseScan,
+    ResumeScan,
     ScanComplete,
+    PreCountComplete(u64),
     Error(String),
+    ScanError(ScanError),
+    ExportErrors,
+    ErrorsExportComplete(Result<String, String>),
     LimitChanged(String),
-    StartScan,
-    ScanUpdate {
-        now_scanned: u64,
-        new_paths_over_limit: Vec<OverLimit>,
+    LimitSettled,
+    DisplayThresholdChanged(String),
+    KeepResultsChanged(String),
+    ErrorLimitChanged(String),
+    MetricSelected(LengthMetric),
+    ProjectLimitLoaded {
+        root: PathBuf,
+        limit: Option<usize>,
     },
+    StartScan,
+    Rescan,
+    ClearResults,
+    ScanUpdate(ScanProgress),
     ExportCsv,
-    CsvExportComplete(Result<String, String>),
+    CsvExportComplete(Result<Exported, String>),
+    ExportJson,
+    ExportMarkdown,
+    AbortExport,
+    LoadBaseline,
+    BaselineLoaded(Result<Baseline, String>),
+    ClearBaseline,
+    MarkdownExportComplete(Result<Exported, String>),
+    JsonExportComplete(Result<Exported, String>),
     LinkPressed(Link),
+    ExportSuggestedNamesToggled(bool),
+    ExportNameLengthsToggled(bool),
+    OpenAfterExportToggled(bool),
+    ExportFilteredToggled(bool),
+    ExportSelectedToggled(bool),
+    SelectionToggled(String, bool),
+    SelectAllVisible,
+    ClearSelection,
+    CopySelected,
+    DelimiterSelected(Delimiter),
+    SeparatorsSelected(Separators),
+    RelativePathsToggled(bool),
+    SuggestPathsToggled(bool),
+    RecurseToggled(bool),
+    FollowSymlinksToggled(bool),
+    SkipHiddenToggled(bool),
+    ScanArchivesToggled(bool),
+    NormalizeUnicodeToggled(bool),
+    IncludeRootsToggled(bool),
+    DeduplicateToggled(bool),
+    CountFirstToggled(bool),
+    EntriesCounted(Option<EntryCount>),
+    MaxDepthChanged(String),
+    UpdateIntervalChanged(String),
+    RetriesChanged(String),
+    MaxDurationChanged(String),
+    /// The scan ran for as long as it may, so it's stopped.
+    TimeLimitReached,
+    ExcludeChanged(String),
+    IncludeChanged(String),
+    ExtensionsChanged(String),
+    ModifiedSinceChanged(String),
+    ModifiedUntilChanged(String),
+    SelectLiveCsv,
+    LiveCsvSelected(Option<PathBuf>),
+    ClearLiveCsv,
+    Recheck,
+    RecheckComplete {
+        remaining: Vec<OverLimit>,
+        fixed: Vec<FixedPath>,
+    },
+    StartRename(String),
+    RenameInputChanged(String),
+    CancelRename,
+    ConfirmRename,
+    RenameComplete(Result<(String, String), String>),
+    /// Renames a fixed path back, see [`UndoRename`].
+    UndoFix(usize),
+    FixUndone(Result<(String, String), String>),
+    SortResults(SortKey),
+    ResultFilterChanged(String),
+    GroupByParentToggled(bool),
+    GroupByDriveToggled(bool),
+    CaseCollisionsToggled(bool),
+    ResultsTabSelected(ResultsTab),
+    TopCountChanged(String),
+    ResultsScrolled(iced::widget::scrollable::Viewport),
+    ToggleGroup(String),
+    CopyPath(String),
+    CopiedExpired(String),
+    CopySummary,
+    SummaryCopiedExpired,
+    RevealPath(String),
+    Shortcut(Shortcut),
+    DiskSpaceQueried(Vec<(PathBuf, DiskSpace)>),
+    /// Sent regularly while scanning, so the rate and the estimate keep moving between updates.
+    Tick,
+    ToggleTheme,
+    WhatIfLimitChanged(u32),
+}
+
+/// Keyboard shortcuts for the main actions.
+#[derive(Debug, Clone, Copy)]
+pub enum Shortcut {
+    /// Ctrl+R
+    StartScan,
+    /// Esc
+    AbortScan,
+    /// Ctrl+E
+    ExportCsv,
 }
 
 pub struct UI {
-    selecting: bool,
-    selected: Option<PathBuf>,
+    /// Saved when the window is closed, so it opens the same way next time.
+    window: WindowState,
+    /// The folder dialog while it's open.
+    selecting: Option<task::Handle>,
+    /// Folders scanned before, the most recent first.
+    recent_folders: Vec<PathBuf>,
+    /// A folder path typed in directly, for when the dialog isn't available.
+    path_input: String,
+    path_error: Option<String>,
+    /// The folders to scan. The first one decides the project limit.
+    roots: Vec<PathBuf>,
+    /// The folder the current results were scanned from.
+    scan_roots: Vec<PathBuf>,
     scan_status: ScanStatus,
+    /// Tells the running scan whether it should pause.
+    scan_paused: Option<watch::Sender<bool>>,
     paths_over_limit: Vec<OverLimit>,
     scanned: u64,
+    files_scanned: u64,
+    directories_scanned: u64,
+    /// How many levels below the scanned folders the deepest directory is.
+    deepest: usize,
+    /// The directory the scan started reading most recently, shown while scanning.
+    current_directory: Option<String>,
+    /// The longest path over the limit found so far, shown while scanning.
+    longest: Option<OverLimit>,
+    /// Counts the entries before measuring them, so the progress is exact from the start.
+    count_first: bool,
+    /// The phase of the running scan if it counts first, see [`UI::count_first`].
+    phase: Option<Phase>,
+    /// Counts a path found several times only once, for overlapping folders or symbolic links.
+    deduplicate: bool,
+    /// The paths over the limit found so far, to recognize duplicates.
+    seen_paths: HashSet<String>,
+    /// How many paths over the limit were left out as duplicates.
+    duplicates: u64,
     limit_input: String,
+    error_limit_input: String,
+    /// Results over this are shown as errors, those below only as warnings.
+    error_limit: Option<usize>,
+    /// When the limit input was last changed. It's applied once typing pauses.
+    limit_edited: Option<Instant>,
     limit: usize,
+    /// Why the limit input is invalid. `limit` keeps the last valid value meanwhile.
+    limit_error: Option<String>,
+    display_threshold_input: String,
+    /// Only results longer than this are shown and exported. `None` shows everything found.
+    display_threshold: Option<usize>,
+    limit_from_project: bool,
     scan_limit: usize,
-    errors: Vec<String>,
+    metric: LengthMetric,
+    scan_metric: LengthMetric,
+    recurse: bool,
+    follow_symlinks: bool,
+    skip_hidden: bool,
+    scan_archives: bool,
+    /// Measures paths composed to Unicode NFC.
+    normalize_unicode: bool,
+    /// Also checks the selected folders themselves.
+    include_roots: bool,
+    max_depth_input: String,
+    max_depth: Option<usize>,
+    max_duration_input: String,
+    /// How long a scan may run before it's stopped. `None` means no limit.
+    max_duration: Option<Duration>,
+    /// Whether the last scan was stopped by [`UI::max_duration`] rather than by the user.
+    time_limited: bool,
+    exclude_input: String,
+    exclude_patterns: Vec<String>,
+    include_input: String,
+    include_patterns: Vec<String>,
+    extensions_input: String,
+    modified_since_input: String,
+    modified_until_input: String,
+    extensions: Vec<String>,
+    /// The extensions the last scan was limited to.
+    scan_extensions: Vec<String>,
+    /// Everything the last scan was started with, to run it again.
+    scan_options: Option<ScanOptions>,
+    scan_setup: Option<ScanSetup>,
+    workers: usize,
+    update_interval_input: String,
+    update_interval: Duration,
+    retries_input: String,
+    retries: u32,
+    errors: ErrorLog,
     exporting: bool,
+    /// Aborts the running export.
+    export_token: Option<CancellationToken>,
     export_message: Option<String>,
     export_success: bool,
+    /// Exports a shortened file name for each result, see [`suggest_name`].
+    export_suggested_names: bool,
+    export_name_lengths: bool,
+    /// Opens reports with the default app once they're exported.
+    open_after_export: bool,
+    /// Exports only the results matching the filter instead of all shown results.
+    export_filtered: bool,
+    /// Exports only the [`UI::selected`] results, this takes precedence over the filter.
+    export_selected: bool,
+    /// Results checked for bulk actions, by path.
+    selected: HashSet<String>,
+    /// The column separator of CSV exports, including the live CSV file.
+    delimiter: Delimiter,
+    /// Shows and exports paths relative to the scanned folder they were found in.
+    relative_paths: bool,
+    /// How the separators of paths are shown and exported.
+    separators: Separators,
+    /// Shows and exports a shortened path for each result, see [`suggest_path`].
+    suggest_paths: bool,
+    /// A CSV file the results are written to while scanning.
+    live_csv: Option<PathBuf>,
+    rechecking: bool,
+    fixed_paths: Vec<FixedPath>,
+    limit_preview: Option<LimitPreview>,
+    memory_usage: Option<u64>,
+    /// The space of the drives the scanned folders are on, queried when the scan starts.
+    disk_space: Vec<(PathBuf, DiskSpace)>,
+    renaming: Option<Rename>,
+    scan_started: Option<Instant>,
+    /// How long the last scan took, set once it completes.
+    scan_elapsed: Option<Duration>,
+    /// Smoothed estimate of how long the running scan still takes.
+    remaining: Option<Duration>,
+    /// Statistics of the last finished scan.
+    summary: Option<ScanSummary>,
+    /// Lengths of all paths of the last scan, not only those over the limit.
+    lengths: LengthHistogram,
+    /// The limit to try against the lengths of the last scan, without scanning again.
+    what_if_limit: u32,
+    /// Number of entries found by the quick count running alongside the scan.
+    precount: Option<u64>,
+    sort: Option<(SortKey, SortDirection)>,
+    result_filter: String,
+    /// Where the results of [`UI::visible_results`] are in `paths_over_limit`, in display order.
+    ///
+    /// Filtering tens of thousands of paths on every frame is slow, so this is only updated when
+    /// the results, the filter or the display threshold change.
+    visible: Vec<usize>,
+    group_by_parent: bool,
+    group_by_drive: bool,
+    detect_case_collisions: bool,
+    case_collisions: Vec<CaseCollision>,
+    results_tab: ResultsTab,
+    top_paths: TopPaths,
+    top_count_input: String,
+    over_limit_totals: OverLimitTotals,
+    /// How many paths over the limit were found on each drive, see [`measure::drive`].
+    drive_counts: BTreeMap<String, u64>,
+    /// The folder names in the paths over the limit, including those no longer kept in memory.
+    segments: SegmentTally,
+    keep_results_input: String,
+    /// Once this many results were found, only the top paths are kept.
+    keep_results: Option<usize>,
+    /// Whether the full list was dropped because it grew past [`UI::keep_results`].
+    results_dropped: bool,
+    /// The scroll offset and height of the results list, so only the visible rows are built.
+    results_viewport: (f32, f32),
+    /// Parent directories or drives whose group is expanded while grouping results.
+    expanded_groups: HashSet<String>,
+    /// An export of an earlier scan the results are compared with.
+    baseline: Option<Baseline>,
+    /// How the results differ from [`UI::baseline`], updated once a scan completes.
+    comparison: Option<Comparison>,
+    /// The path which was just copied to the clipboard.
+    copied: Option<String>,
+    /// Whether the summary was just copied to the clipboard.
+    summary_copied: bool,
+    theme: Theme,
+}
+
+/// A folder in the recent folders list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentFolder(PathBuf);
+
+impl fmt::Display for RecentFolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+/// Which of the reports of a scan is shown below the controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsTab {
+    OverLimit,
+    /// The longest paths, see [`TopPaths`].
+    TopPaths,
+    CaseCollisions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Path,
+    Length,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// A result which is currently being renamed inline.
+struct Rename {
+    path: String,
+    new_name: String,
+    error: Option<String>,
+    applying: bool,
+}
+
+/// How many results of the last scan would be over the limit currently typed in.
+struct LimitPreview {
+    limit: usize,
+    count: u64,
+}
+
+/// The phases of a scan which counts its entries first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Counting,
+    Measuring,
+}
+
+/// How a scan runs besides its [`ScanOptions`], taken when it starts so a rescan runs the same.
+#[derive(Debug, Clone)]
+struct ScanSetup {
+    max_duration: Option<Duration>,
+    count_first: bool,
+    deduplicate: bool,
+    /// The file the results are written to while scanning, and its columns.
+    live_csv: Option<(PathBuf, export::CsvOptions)>,
 }
 
 enum ScanStatus {
     WaitingForStart,
     Scanning(CancellationToken),
     Done,
+    /// Stopped before every folder was read, so the results are partial.
+    Aborted,
 }
 
 impl ScanStatus {
@@ -49,7 +418,7 @@ impl ScanStatus {
         match self {
             ScanStatus::WaitingForStart => true,
             ScanStatus::Scanning(_) => false,
-            ScanStatus::Done => true,
+            ScanStatus::Done | ScanStatus::Aborted => true,
         }
     }
 
@@ -57,204 +426,916 @@ impl ScanStatus {
         match self {
             ScanStatus::WaitingForStart => false,
             ScanStatus::Scanning(_) => true,
-            ScanStatus::Done => false,
+            ScanStatus::Done | ScanStatus::Aborted => false,
         }
     }
 
+    /// Whether the scan stopped, completely or aborted, so its results can be used.
     fn is_done(&self) -> bool {
         match self {
             ScanStatus::WaitingForStart => false,
             ScanStatus::Scanning(_) => false,
-            ScanStatus::Done => true,
+            ScanStatus::Done | ScanStatus::Aborted => true,
         }
     }
 
-    fn cancel(&mut self) {
+    fn is_aborted(&self) -> bool {
+        matches!(self, ScanStatus::Aborted)
+    }
+
+    fn is_cancelling(&self) -> bool {
+        match self {
+            ScanStatus::WaitingForStart => false,
+            ScanStatus::Scanning(cancellation_token) => cancellation_token.is_cancelled(),
+            ScanStatus::Done | ScanStatus::Aborted => false,
+        }
+    }
+
+    /// Asks a running scan to stop.
+    ///
+    /// The status only changes to [`ScanStatus::Aborted`] once the scan reports completion,
+    /// so its last results are still delivered.
+    fn cancel(&self) {
         match self {
             ScanStatus::WaitingForStart => (),
-            ScanStatus::Scanning(cancellation_token) => {
-                cancellation_token.cancel();
-                *self = Self::Done;
-            }
-            ScanStatus::Done => (),
+            ScanStatus::Scanning(cancellation_token) => cancellation_token.cancel(),
+            ScanStatus::Done | ScanStatus::Aborted => (),
+        }
+    }
+}
+
+/// Running totals over all paths over the limit, including those no longer kept in memory.
+#[derive(Debug, Clone, Copy, Default)]
+struct OverLimitTotals {
+    count: u64,
+    length: u64,
+    file_bytes: u64,
+}
+
+/// Statistics of a finished scan.
+#[derive(Debug, Clone)]
+struct ScanSummary {
+    scanned: u64,
+    over_limit: u64,
+    /// The longest paths, the longest first.
+    longest: Vec<OverLimit>,
+    /// The average length of the paths over the limit.
+    average_over_limit: f64,
+    /// The combined size of the files over the limit.
+    file_bytes: u64,
+    /// How many paths over the limit are on each drive.
+    drives: Vec<(String, u64)>,
+    /// The folder names contributing the most length to the paths over the limit.
+    common_segments: Vec<Segment>,
+    deepest: usize,
+    /// How many paths over the limit were found more than once and only counted once.
+    duplicates: u64,
+    /// Whether the scan was stopped because it reached the time limit, `aborted` is set then too.
+    time_limited: bool,
+    /// How long the scan ran, up to the abort if it was aborted.
+    elapsed: Duration,
+    aborted: bool,
+}
+
+impl ScanSummary {
+    fn percentage_over_limit(&self) -> f64 {
+        if self.scanned == 0 {
+            0.0
+        } else {
+            self.over_limit as f64 / self.scanned as f64 * 100.0
         }
     }
 }
 
+/// A report which was written successfully.
+#[derive(Debug, Clone)]
+pub struct Exported {
+    message: String,
+    file: PathBuf,
+}
+
+/// A previously over-limit path that has been resolved during this session.
 #[derive(Debug, Clone)]
-pub struct OverLimit {
+pub struct FixedPath {
     path: String,
-    size: u64,
+    before: u64,
+    /// The new length, if known. `None` means the path no longer exists.
+    after: Option<u64>,
+    /// Only renames made in the app can be undone, anything else was changed elsewhere.
+    undo: Option<UndoRename>,
+}
+
+/// How to bring back a path that was renamed in the app.
+///
+/// Undoing renames the file or folder back, which brings back every path it fixed.
+#[derive(Debug, Clone)]
+pub struct UndoRename {
+    old_path: String,
+    new_path: String,
+    /// The result as it was before the rename.
+    original: OverLimit,
 }
 
 impl UI {
+    /// Opens the window the way it was last closed.
+    ///
+    /// Closing is handled by the app, so the window can be saved first.
+    pub fn window_settings() -> window::Settings {
+        window::Settings {
+            exit_on_close_request: false,
+            ..Settings::load()
+                .window
+                .unwrap_or_default()
+                .window_settings()
+        }
+    }
+
     pub fn start() -> (Self, Task<Message>) {
+        let settings = Settings::load();
+        let roots: Vec<_> = settings
+            .last_folder
+            .filter(|folder| folder.is_dir())
+            .into_iter()
+            .collect();
+        let task = roots
+            .first()
+            .cloned()
+            .map(load_project_limit)
+            .unwrap_or_else(Task::none);
+
         (
             Self {
-                selecting: false,
-                selected: None,
+                window: settings.window.unwrap_or_default(),
+                selecting: None,
+                recent_folders: settings.recent_folders,
+                path_input: String::new(),
+                path_error: None,
+                roots,
+                scan_roots: Vec::new(),
                 scan_status: ScanStatus::WaitingForStart,
+                scan_paused: None,
                 paths_over_limit: Vec::new(),
                 scanned: 0,
-                limit_input: "240".to_string(),
-                limit: 240,
-                scan_limit: 240,
-                errors: Vec::new(),
+                files_scanned: 0,
+                directories_scanned: 0,
+                deepest: 0,
+                current_directory: None,
+                limit_input: settings.limit.to_string(),
+                limit_edited: None,
+                error_limit_input: String::new(),
+                error_limit: None,
+                limit: settings.limit,
+                limit_error: None,
+                display_threshold_input: String::new(),
+                display_threshold: None,
+                limit_from_project: false,
+                scan_limit: settings.limit,
+                metric: settings.metric,
+                scan_metric: settings.metric,
+                recurse: true,
+                follow_symlinks: false,
+                skip_hidden: false,
+                scan_archives: false,
+                normalize_unicode: false,
+                include_roots: false,
+                longest: None,
+                count_first: false,
+                phase: None,
+                deduplicate: true,
+                seen_paths: HashSet::new(),
+                duplicates: 0,
+                max_depth_input: String::new(),
+                max_depth: None,
+                max_duration_input: String::new(),
+                max_duration: None,
+                time_limited: false,
+                exclude_input: String::new(),
+                exclude_patterns: Vec::new(),
+                include_input: String::new(),
+                include_patterns: Vec::new(),
+                extensions_input: String::new(),
+                modified_since_input: String::new(),
+                modified_until_input: String::new(),
+                extensions: Vec::new(),
+                scan_extensions: Vec::new(),
+                scan_options: None,
+                scan_setup: None,
+                workers: ScanOptions::default_workers(),
+                update_interval_input: ScanOptions::DEFAULT_UPDATE_INTERVAL.as_millis().to_string(),
+                update_interval: ScanOptions::DEFAULT_UPDATE_INTERVAL,
+                retries_input: ScanOptions::DEFAULT_RETRIES.to_string(),
+                retries: ScanOptions::DEFAULT_RETRIES,
+                errors: ErrorLog::default(),
                 exporting: false,
+                export_token: None,
                 export_message: None,
                 export_success: false,
+                export_suggested_names: true,
+                export_name_lengths: false,
+                open_after_export: false,
+                export_filtered: false,
+                export_selected: false,
+                selected: HashSet::new(),
+                delimiter: Delimiter::default(),
+                relative_paths: false,
+                separators: Separators::default(),
+                suggest_paths: false,
+                live_csv: None,
+                rechecking: false,
+                fixed_paths: Vec::new(),
+                limit_preview: None,
+                memory_usage: None,
+                disk_space: Vec::new(),
+                renaming: None,
+                scan_started: None,
+                scan_elapsed: None,
+                remaining: None,
+                summary: None,
+                lengths: LengthHistogram::default(),
+                what_if_limit: settings.limit.min(LengthHistogram::MAX_LENGTH) as u32,
+                precount: None,
+                sort: None,
+                result_filter: String::new(),
+                visible: Vec::new(),
+                group_by_parent: false,
+                group_by_drive: false,
+                detect_case_collisions: false,
+                case_collisions: Vec::new(),
+                results_tab: ResultsTab::OverLimit,
+                top_paths: TopPaths::default(),
+                top_count_input: TopPaths::DEFAULT_CAPACITY.to_string(),
+                over_limit_totals: OverLimitTotals::default(),
+                drive_counts: BTreeMap::new(),
+                segments: SegmentTally::default(),
+                keep_results_input: String::new(),
+                keep_results: None,
+                results_dropped: false,
+                results_viewport: (0.0, 800.0),
+                expanded_groups: HashSet::new(),
+                baseline: None,
+                comparison: None,
+                copied: None,
+                summary_copied: false,
+                theme: Theme::Light,
             },
-            Task::none(),
+            task,
         )
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::SelectFolder => {
-                self.selecting = true;
-                Task::future(async {
-                    let folder = AsyncFileDialog::new().pick_folder().await;
-                    Message::SelectedFolder(folder.map(Arc::new))
-                })
-            }
+            Message::SelectFolder => self.pick_folder(Message::SelectedFolder),
             Message::SelectedFolder(selected) => {
-                self.selecting = false;
+                self.selecting = None;
                 if let Some(selected) = selected {
                     if let Some(selected) = Arc::into_inner(selected) {
-                        let selected: PathBuf = selected.path().into();
-                        self.selected = Some(selected.clone());
-                        self.scan_status = ScanStatus::WaitingForStart;
+                        return self.select_root(selected.path().into());
+                    }
+                }
+                Task::none()
+            }
+            Message::PathInputChanged(path) => {
+                self.path_input = path;
+                self.path_error = None;
+                Task::none()
+            }
+            Message::UsePath => {
+                let path = PathBuf::from(self.path_input.trim());
+                if path.as_os_str().is_empty() {
+                    return Task::none();
+                }
+                self.choose_folder(path, false)
+            }
+            Message::RecentFolderSelected(RecentFolder(path)) => self.choose_folder(path, false),
+            Message::FileDropped(path) => self.choose_folder(path, true),
+            Message::WindowMoved(position) => {
+                // Windows moves minimized windows far off-screen
+                if position.x > -32_000.0 && position.y > -32_000.0 {
+                    self.window.position = Some((position.x, position.y));
+                }
+                Task::none()
+            }
+            Message::WindowResized(size) => {
+                // Minimized windows have no size
+                if size.width > 0.0 && size.height > 0.0 {
+                    self.window.width = size.width;
+                    self.window.height = size.height;
+                }
+                Task::none()
+            }
+            Message::WindowCloseRequested(id) => {
+                // Only the window changes, the rest is saved when a scan starts
+                let settings = Settings {
+                    window: Some(self.window),
+                    ..Settings::load()
+                };
+                // The app is closing either way, there's no one left to show an error to
+                Task::future(settings.save()).then(move |_| window::close(id))
+            }
+            Message::PathChecked(result) => match result {
+                Ok(path) => {
+                    self.path_input.clear();
+                    self.select_root(path)
+                }
+                Err(err) => {
+                    self.path_error = Some(err);
+                    Task::none()
+                }
+            },
+            Message::AddFolder => self.pick_folder(Message::AddedFolder),
+            Message::CancelSelection => {
+                if let Some(dialog) = self.selecting.take() {
+                    dialog.abort();
+                }
+                Task::none()
+            }
+            Message::AddedFolder(added) => {
+                self.selecting = None;
+                let Some(added) = added.and_then(Arc::into_inner) else {
+                    return Task::none();
+                };
+                let added: PathBuf = added.path().into();
+                if self.roots.contains(&added) {
+                    return Task::none();
+                }
+                self.roots.push(added);
+                if self.roots.len() == 1 {
+                    load_project_limit(self.roots[0].clone())
+                } else {
+                    Task::none()
+                }
+            }
+            Message::RemoveFolder(folder) => {
+                let was_first = self.roots.first() == Some(&folder);
+                self.roots.retain(|root| *root != folder);
+                match self.roots.first() {
+                    Some(first) if was_first => load_project_limit(first.clone()),
+                    _ => {
+                        if self.roots.is_empty() {
+                            self.limit_from_project = false;
+                        }
+                        Task::none()
+                    }
+                }
+            }
+            Message::ProjectLimitLoaded { root, limit } => {
+                // Ignore the result if another folder was selected in the meantime
+                if self.roots.first() == Some(&root) {
+                    if let Some(limit) = limit {
+                        self.limit = limit;
+                        self.limit_input = limit.to_string();
+                        self.limit_error = None;
+                        self.limit_from_project = true;
+                        self.update_limit_preview();
+                    } else {
+                        self.limit_from_project = false;
                     }
                 }
                 Task::none()
             }
-            Message::AbortScan | Message::ScanComplete => {
+            Message::ClearResults => {
+                if !self.scan_status.is_scanning() && !self.rechecking {
+                    self.clear_results();
+                    self.scan_status = ScanStatus::WaitingForStart;
+                }
+                Task::none()
+            }
+            Message::PauseScan => {
+                if let Some(paused) = &self.scan_paused {
+                    paused.send_replace(true);
+                }
+                Task::none()
+            }
+            Message::ResumeScan => {
+                if let Some(paused) = &self.scan_paused {
+                    paused.send_replace(false);
+                }
+                Task::none()
+            }
+            Message::WhatIfLimitChanged(limit) => {
+                self.what_if_limit = limit;
+                Task::none()
+            }
+            Message::ToggleTheme => {
+                self.theme = match self.theme {
+                    Theme::Dark => Theme::Light,
+                    _ => Theme::Dark,
+                };
+                Task::none()
+            }
+            Message::DiskSpaceQueried(disk_space) => {
+                self.disk_space = disk_space;
+                Task::none()
+            }
+            Message::Tick => {
+                // Counts down between the updates, which make a new estimate
+                self.remaining = self
+                    .remaining
+                    .map(|remaining| remaining.saturating_sub(TICK_INTERVAL));
+                Task::none()
+            }
+            Message::Shortcut(shortcut) => {
+                let message = match shortcut {
+                    Shortcut::StartScan => self.can_start_scan().then_some(Message::StartScan),
+                    Shortcut::AbortScan => self.can_abort_scan().then_some(Message::AbortScan),
+                    Shortcut::ExportCsv => self.can_export().then_some(Message::ExportCsv),
+                };
+                match message {
+                    Some(message) => self.update(message),
+                    None => Task::none(),
+                }
+            }
+            Message::AbortScan => {
+                self.scan_status.cancel();
+                self.precount = None;
+                self.remaining = None;
+                Task::none()
+            }
+            Message::ScanComplete => {
+                let aborted = self.scan_status.is_cancelling();
+                // Also stops the pre-count, if it's still running
                 self.scan_status.cancel();
+                self.scan_status = if aborted {
+                    ScanStatus::Aborted
+                } else {
+                    ScanStatus::Done
+                };
+                self.scan_paused = None;
+                self.sort_results();
+                self.refresh_visible();
+                self.precount = None;
+                self.phase = None;
+                self.current_directory = None;
+                self.scan_elapsed = self.scan_started.map(|started| started.elapsed());
+                self.remaining = None;
+                self.summary = Some(self.summarize(aborted));
+                self.update_comparison();
+                Task::none()
+            }
+            Message::EntriesCounted(count) => {
+                let Some(count) = count else {
+                    // Aborted while counting, so there's nothing to measure
+                    return self.update(Message::ScanComplete);
+                };
+                let (Some(options), ScanStatus::Scanning(token), Some(paused)) =
+                    (&self.scan_options, &self.scan_status, &self.scan_paused)
+                else {
+                    return Task::none();
+                };
+                let options = ScanOptions {
+                    expected: Some(count),
+                    ..options.clone()
+                };
+                let token = token.clone();
+                let paused = paused.subscribe();
+
+                self.phase = Some(Phase::Measuring);
+                self.precount = Some(count.total());
+                // The rate and the estimate should only cover measuring
+                self.scan_started = Some(Instant::now());
+                self.start_scan(self.scan_roots.clone(), options, token, paused)
+            }
+            Message::PreCountComplete(total) => {
+                if self.scan_status.is_scanning() && !self.scan_status.is_cancelling() {
+                    self.precount = Some(total);
+                }
                 Task::none()
             }
             Message::Error(err) => {
+                self.errors.push_message(io::ErrorKind::Other, err);
+                Task::none()
+            }
+            Message::ScanError(err) => {
                 self.errors.push(err);
                 Task::none()
             }
+            Message::ExportErrors => {
+                let token = self.start_export();
+                let errors = self.errors.clone();
+                Task::future(async move {
+                    let file_handle = AsyncFileDialog::new()
+                        .set_file_name("path_length_errors.txt")
+                        .add_filter("Text", &["txt"])
+                        .save_file()
+                        .await;
+
+                    let Some(file_handle) = file_handle else {
+                        return Message::ErrorsExportComplete(Err("Export cancelled".to_string()));
+                    };
+                    let file_path = file_handle.path().to_path_buf();
+                    let replaced = replaced_note(&file_path).await;
+
+                    Message::ErrorsExportComplete(
+                        abortable_write(
+                            token,
+                            &file_path,
+                            export::write_errors(&file_path, &errors),
+                        )
+                        .await
+                        .map(|()| {
+                            format!(
+                                "Exported {} errors to {}{}",
+                                errors.total(),
+                                file_path.display(),
+                                replaced
+                            )
+                        }),
+                    )
+                })
+            }
             Message::LimitChanged(limit) => {
-                self.limit_input = limit.clone();
-                if let Ok(parsed) = limit.parse::<usize>() {
-                    self.limit = parsed;
+                self.limit_input = limit;
+                self.limit_from_project = false;
+                self.limit_edited = Some(Instant::now());
+                Task::future(async {
+                    tokio::time::sleep(LIMIT_DEBOUNCE).await;
+                    Message::LimitSettled
+                })
+            }
+            Message::LimitSettled => {
+                // Only the timer of the last change finds the input untouched for long enough
+                if self
+                    .limit_edited
+                    .is_some_and(|edited| edited.elapsed() >= LIMIT_DEBOUNCE)
+                {
+                    self.apply_limit_input();
+                }
+                Task::none()
+            }
+            Message::ErrorLimitChanged(error_limit) => {
+                if error_limit.trim().is_empty() {
+                    self.error_limit = None;
+                } else if let Ok(parsed) = error_limit.trim().parse::<usize>() {
+                    self.error_limit = Some(parsed);
+                }
+                self.error_limit_input = error_limit;
+                Task::none()
+            }
+            Message::KeepResultsChanged(keep) => {
+                if keep.trim().is_empty() {
+                    self.keep_results = None;
+                } else if let Ok(parsed) = keep.trim().parse::<usize>() {
+                    self.keep_results = Some(parsed);
                 }
+                self.keep_results_input = keep;
+                Task::none()
+            }
+            Message::DisplayThresholdChanged(threshold) => {
+                if threshold.trim().is_empty() {
+                    self.display_threshold = None;
+                } else if let Ok(parsed) = threshold.trim().parse::<usize>() {
+                    self.display_threshold = Some(parsed);
+                }
+                self.display_threshold_input = threshold;
+                self.refresh_visible();
                 Task::none()
             }
             Message::StartScan => {
-                if let Some(ref folder) = self.selected {
-                    self.scan_status.cancel();
-                    self.paths_over_limit.clear();
-                    self.errors.clear();
-                    self.scanned = 0;
-                    self.export_message = None;
-                    let token = CancellationToken::new();
-                    self.scan_status = ScanStatus::Scanning(token.clone());
-                    self.scan_limit = self.limit;
-                    self.start_scan(folder.clone(), self.limit, token)
+                // Pressing enter right after typing shouldn't scan with the previous limit
+                if self.limit_edited.is_some() {
+                    self.apply_limit_input();
+                }
+                // A previous scan has to finish first, otherwise its final results would mix in
+                if !self.can_start_scan() {
+                    Task::none()
+                } else if let Some(first) = self.roots.first().cloned() {
+                    self.clear_results();
+                    let excludes =
+                        compile_patterns(&self.exclude_patterns, "exclude", &mut self.errors);
+                    let includes =
+                        compile_patterns(&self.include_patterns, "include", &mut self.errors);
+                    let mut parse_date = |input: &str| {
+                        if input.trim().is_empty() {
+                            return None;
+                        }
+                        date::parse_date(input)
+                            .inspect_err(|err| {
+                                self.errors
+                                    .push_message(io::ErrorKind::InvalidInput, err.clone())
+                            })
+                            .ok()
+                    };
+                    let modified_since = parse_date(&self.modified_since_input);
+                    let modified_until = parse_date(&self.modified_until_input);
+
+                    let options = ScanOptions {
+                        limit: self.limit,
+                        metric: self.metric,
+                        normalize_unicode: self.normalize_unicode,
+                        recurse: self.recurse,
+                        follow_symlinks: self.follow_symlinks,
+                        max_depth: self.max_depth,
+                        include_roots: self.include_roots,
+                        excludes,
+                        includes,
+                        ignores: Vec::new(),
+                        skip_hidden: self.skip_hidden,
+                        extensions: self.extensions.clone(),
+                        modified_since,
+                        modified_until,
+                        detect_case_collisions: self.detect_case_collisions,
+                        scan_archives: self.scan_archives,
+                        workers: self.workers,
+                        update_interval: self.update_interval,
+                        batch_size: ScanOptions::DEFAULT_BATCH_SIZE,
+                        retries: self.retries,
+                        expected: None,
+                    };
+                    // Reversed, so the first folder ends up on top
+                    for root in self.roots.iter().rev() {
+                        settings::remember_folder(&mut self.recent_folders, root);
+                    }
+                    let settings = Settings {
+                        last_folder: Some(first),
+                        recent_folders: self.recent_folders.clone(),
+                        limit: self.limit,
+                        metric: self.metric,
+                        window: Some(self.window),
+                    };
+
+                    let setup = self.scan_setup(&self.roots, &options);
+                    Task::batch([
+                        self.run_scan(self.roots.clone(), options, setup),
+                        Task::future(settings.save()).then(|result| match result {
+                            Ok(()) => Task::none(),
+                            Err(err) => Task::done(Message::Error(err)),
+                        }),
+                    ])
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Rescan => {
+                if let Some(options) = self.scan_options.clone()
+                    && let Some(setup) = self.scan_setup.clone()
+                    && self.can_rescan()
+                {
+                    self.clear_results();
+                    self.run_scan(self.scan_roots.clone(), options, setup)
                 } else {
                     Task::none()
                 }
             }
-            Message::ScanUpdate {
-                now_scanned,
-                new_paths_over_limit,
-            } => {
-                self.scanned = now_scanned;
-                self.paths_over_limit.extend(new_paths_over_limit);
+            Message::ScanUpdate(mut progress) => {
+                // Updates may arrive out of order, the count should never go backwards
+                self.scanned = self.scanned.max(progress.scanned);
+                self.files_scanned = self.files_scanned.max(progress.files);
+                self.directories_scanned = self.directories_scanned.max(progress.directories);
+                self.deepest = self.deepest.max(progress.deepest);
+                if progress.current_directory.is_some() {
+                    self.current_directory = progress.current_directory.clone();
+                }
+                // Out of order updates may carry an older, shorter one
+                if let Some(longest) = progress.longest.take()
+                    && self
+                        .longest
+                        .as_ref()
+                        .is_none_or(|current| longest.size > current.size)
+                {
+                    self.longest = Some(longest);
+                }
+                self.update_remaining(progress.directories_read, progress.directories_pending);
+                if self
+                    .scan_setup
+                    .as_ref()
+                    .is_some_and(|setup| setup.deduplicate)
+                {
+                    // The live CSV file still gets the duplicates
+                    progress.over_limit.retain(|path| {
+                        let new = self.seen_paths.insert(path.path.clone());
+                        if !new {
+                            self.duplicates += 1;
+                        }
+                        new
+                    });
+                }
+                for path in &progress.over_limit {
+                    self.count_result(path);
+                }
+                if !self.results_dropped
+                    && self.keep_results.is_some_and(|keep| {
+                        self.paths_over_limit.len() + progress.over_limit.len() > keep
+                    })
+                {
+                    // The live CSV file still gets everything
+                    self.results_dropped = true;
+                    self.paths_over_limit = Vec::new();
+                    self.results_tab = ResultsTab::TopPaths;
+                }
+                let first_new = self.paths_over_limit.len();
+                if !self.results_dropped {
+                    self.paths_over_limit.extend(progress.over_limit);
+                }
+                self.case_collisions.extend(progress.case_collisions);
+                self.lengths.merge(&progress.lengths);
+                // Sorted once the scan is complete, see `sort_results`
+                if self.results_dropped {
+                    self.visible.clear();
+                } else {
+                    self.extend_visible(first_new);
+                }
+                self.memory_usage = memory::resident_set_size();
                 Task::none()
             }
             Message::ExportCsv => {
-                if self.paths_over_limit.is_empty() {
+                let paths_to_export: Vec<_> = self.exported_results().cloned().collect();
+                if paths_to_export.is_empty() {
                     Task::none()
                 } else {
-                    self.exporting = true;
-                    self.export_message = None;
-                    let paths_to_export = self.paths_over_limit.clone();
+                    let token = self.start_export();
+                    let options = export::CsvOptions {
+                        limit: self.scan_limit,
+                        metric: self.scan_metric,
+                        suggested_names: self.export_suggested_names,
+                        name_lengths: self.export_name_lengths,
+                        roots: self.scan_roots.len() > 1,
+                        error_limit: self.error_limit,
+                        suggested_paths: self.suggest_paths,
+                        relative: self.relative_paths,
+                        delimiter: self.delimiter,
+                        separators: self.separators,
+                    };
+                    let (extension, filter) = self.delimiter.file_type();
                     Task::future(async move {
                         let file_handle = AsyncFileDialog::new()
-                            .set_file_name("path_length_report.csv")
-                            .add_filter("CSV", &["csv"])
+                            .set_file_name(format!("path_length_report.{}", extension))
+                            .add_filter(filter, &[extension])
                             .save_file()
                             .await;
 
-                        if let Some(file_handle) = file_handle {
-                            let export_count = paths_to_export.len();
-                            let file_path = file_handle.path().to_path_buf();
-
-                            match tokio::fs::File::create(&file_path).await {
-                                Ok(mut file) => {
-                                    // Write CSV header
-                                    if let Err(e) = file.write_all(b"Length;Path\n").await {
-                                        return Message::CsvExportComplete(Err(format!(
-                                            "Failed to write CSV header: {}",
-                                            e
-                                        )));
-                                    }
+                        let Some(file_handle) = file_handle else {
+                            return Message::CsvExportComplete(Err("Export cancelled".to_string()));
+                        };
+                        let file_path = file_handle.path().to_path_buf();
+                        let replaced = replaced_note(&file_path).await;
 
-                                    // Write in chunks of 1000 lines
-                                    for chunk in paths_to_export.chunks(1000) {
-                                        let mut chunk_content = String::new();
-                                        for path in chunk {
-                                            chunk_content.push_str(&format!(
-                                                "{};\"{}\"\n",
-                                                path.size,
-                                                path.path
-                                                    .replace("\\", "\\\\")
-                                                    .replace("\"", "\"\""),
-                                            ));
-                                        }
-
-                                        if let Err(e) =
-                                            file.write_all(chunk_content.as_bytes()).await
-                                        {
-                                            return Message::CsvExportComplete(Err(format!(
-                                                "Failed to write CSV chunk: {}",
-                                                e
-                                            )));
-                                        }
-                                    }
+                        Message::CsvExportComplete(
+                            abortable_write(
+                                token,
+                                &file_path,
+                                export::write_csv(&file_path, &paths_to_export, &options),
+                            )
+                            .await
+                            .map(|()| Exported {
+                                message: format!(
+                                    "Exported {} paths to {}{}",
+                                    paths_to_export.len(),
+                                    file_path.display(),
+                                    replaced
+                                ),
+                                file: file_path.clone(),
+                            }),
+                        )
+                    })
+                }
+            }
+            Message::ExportJson => {
+                if self.exported_results().next().is_none() {
+                    Task::none()
+                } else {
+                    let token = self.start_export();
+                    let report = self.json_report();
+                    Task::future(async move {
+                        let file_handle = AsyncFileDialog::new()
+                            .set_file_name("path_length_report.json")
+                            .add_filter("JSON", &["json"])
+                            .save_file()
+                            .await;
 
-                                    if let Err(e) = file.flush().await {
-                                        return Message::CsvExportComplete(Err(format!(
-                                            "Failed to flush CSV file: {}",
-                                            e
-                                        )));
-                                    }
+                        let Some(file_handle) = file_handle else {
+                            return Message::JsonExportComplete(
+                                Err("Export cancelled".to_string()),
+                            );
+                        };
+                        let file_path = file_handle.path().to_path_buf();
+                        let replaced = replaced_note(&file_path).await;
 
-                                    Message::CsvExportComplete(Ok(format!(
-                                        "Exported {} paths to {}",
-                                        export_count,
-                                        file_path.display()
-                                    )))
-                                }
-                                Err(e) => Message::CsvExportComplete(Err(format!(
-                                    "Failed to create CSV file: {}",
+                        let json = match serde_json::to_vec_pretty(&report) {
+                            Ok(json) => json,
+                            Err(e) => {
+                                return Message::JsonExportComplete(Err(format!(
+                                    "Failed to serialize JSON report: {}",
                                     e
-                                ))),
+                                )));
                             }
-                        } else {
-                            Message::CsvExportComplete(Err("Export cancelled".to_string()))
+                        };
+
+                        let write = async {
+                            fs::write(&file_path, json)
+                                .await
+                                .map_err(|e| format!("Failed to write JSON file: {}", e))
+                        };
+                        if let Err(err) = abortable_write(token, &file_path, write).await {
+                            return Message::JsonExportComplete(Err(err));
                         }
+
+                        Message::JsonExportComplete(Ok(Exported {
+                            message: format!(
+                                "Exported {} paths to {}{}",
+                                report.paths.len(),
+                                file_path.display(),
+                                replaced
+                            ),
+                            file: file_path,
+                        }))
+                    })
+                }
+            }
+            Message::ExportMarkdown => {
+                if self.exported_results().next().is_none() {
+                    Task::none()
+                } else {
+                    let token = self.start_export();
+                    let report = self.json_report();
+                    Task::future(async move {
+                        let file_handle = AsyncFileDialog::new()
+                            .set_file_name("path_length_report.md")
+                            .add_filter("Markdown", &["md"])
+                            .save_file()
+                            .await;
+
+                        let Some(file_handle) = file_handle else {
+                            return Message::MarkdownExportComplete(Err(
+                                "Export cancelled".to_string()
+                            ));
+                        };
+                        let file_path = file_handle.path().to_path_buf();
+                        let replaced = replaced_note(&file_path).await;
+
+                        Message::MarkdownExportComplete(
+                            abortable_write(
+                                token,
+                                &file_path,
+                                export::write_markdown(&file_path, &report),
+                            )
+                            .await
+                            .map(|()| Exported {
+                                message: format!(
+                                    "Exported {} paths to {}{}",
+                                    report.paths.len(),
+                                    file_path.display(),
+                                    replaced
+                                ),
+                                file: file_path.clone(),
+                            }),
+                        )
                     })
                 }
             }
-            Message::CsvExportComplete(result) => {
-                self.exporting = false;
+            Message::AbortExport => {
+                if let Some(token) = &self.export_token {
+                    token.cancel();
+                }
+                Task::none()
+            }
+            Message::LoadBaseline => Task::future(async {
+                let file_handle = AsyncFileDialog::new()
+                    .add_filter("Exports", &["csv", "tsv", "json"])
+                    .pick_file()
+                    .await;
+                match file_handle {
+                    Some(handle) => Some(Message::BaselineLoaded(
+                        Baseline::load(handle.path().to_path_buf()).await,
+                    )),
+                    None => None,
+                }
+            })
+            .and_then(Task::done),
+            Message::BaselineLoaded(result) => {
                 match result {
-                    Ok(success_msg) => {
-                        self.export_message = Some(success_msg);
-                        self.export_success = true;
-                        Task::none()
-                    }
-                    Err(error_msg) => {
-                        self.export_message = Some(error_msg);
-                        self.export_success = false;
-                        Task::none()
+                    Ok(baseline) => {
+                        self.baseline = Some(baseline);
+                        self.update_comparison();
                     }
+                    Err(err) => self.errors.push_message(io::ErrorKind::InvalidData, err),
+                }
+                Task::none()
+            }
+            Message::ClearBaseline => {
+                self.baseline = None;
+                self.comparison = None;
+                Task::none()
+            }
+            Message::CsvExportComplete(result)
+            | Message::JsonExportComplete(result)
+            | Message::MarkdownExportComplete(result) => {
+                let file = result.as_ref().ok().map(|exported| exported.file.clone());
+                self.finish_export(result.map(|exported| exported.message));
+                // The report is written either way, not being able to open it is a separate issue
+                if self.open_after_export
+                    && let Some(file) = file
+                    && let Err(err) = open::that_detached(&file)
+                {
+                    self.errors.push_message(
+                        err.kind(),
+                        format!("Failed to open {}: {}", file.display(), err),
+                    );
                 }
+                Task::none()
+            }
+            Message::ErrorsExportComplete(result) => {
+                self.finish_export(result);
+                Task::none()
             }
             Message::LinkPressed(link) => {
                 let _ = open::that_in_background(match link {
@@ -265,206 +1346,2656 @@ impl UI {
 
                 Task::none()
             }
-        }
-    }
-
-    pub fn view(&self) -> iced::Element<'_, Message> {
-        use iced::widget::{column, *};
-
-        let main_controls = column![
-            row![
-                button(text("Select Folder")).on_press_maybe(if self.selecting {
-                    None
-                } else {
-                    Some(Message::SelectFolder)
-                }),
-                if let Some(selected) = &self.selected {
-                    text(selected.to_string_lossy())
-                } else {
-                    text("")
+            Message::SelectLiveCsv => {
+                let (extension, filter) = self.delimiter.file_type();
+                Task::future(async move {
+                    let file_handle = AsyncFileDialog::new()
+                        .set_file_name(format!("path_length_report.{}", extension))
+                        .add_filter(filter, &[extension])
+                        .save_file()
+                        .await;
+                    Message::LiveCsvSelected(file_handle.map(|handle| handle.path().to_path_buf()))
+                })
+            }
+            Message::LiveCsvSelected(path) => {
+                if path.is_some() {
+                    self.live_csv = path;
                 }
-            ]
-            .spacing(10)
-            .align_y(Vertical::Center),
-            row![
-                text("Path Length Limit:"),
-                text_input("", &self.limit_input)
-                    .on_input(Message::LimitChanged)
-                    .on_submit(Message::StartScan)
-                    .width(Length::Fixed(100.0)),
-            ]
-            .spacing(10)
-            .align_y(Vertical::Center),
-            row![
-                button(text("Start Scan")).on_press_maybe(
-                    if self.selected.is_some() && !self.scan_status.is_scanning() {
-                        Some(Message::StartScan)
-                    } else {
-                        None
-                    }
-                ),
-                button(text("Abort")).on_press_maybe(if self.scan_status.is_scanning() {
-                    Some(Message::AbortScan)
+                Task::none()
+            }
+            Message::ClearLiveCsv => {
+                self.live_csv = None;
+                Task::none()
+            }
+            Message::SuggestPathsToggled(enabled) => {
+                self.suggest_paths = enabled;
+                Task::none()
+            }
+            Message::RelativePathsToggled(enabled) => {
+                self.relative_paths = enabled;
+                Task::none()
+            }
+            Message::SeparatorsSelected(separators) => {
+                self.separators = separators;
+                Task::none()
+            }
+            Message::DelimiterSelected(delimiter) => {
+                self.delimiter = delimiter;
+                Task::none()
+            }
+            Message::ExportSelectedToggled(enabled) => {
+                self.export_selected = enabled;
+                Task::none()
+            }
+            Message::SelectionToggled(path, selected) => {
+                if selected {
+                    self.selected.insert(path);
                 } else {
-                    None
-                }),
-                button(text("Export CSV")).on_press_maybe(
-                    if !self.paths_over_limit.is_empty()
-                        && !self.exporting
+                    self.selected.remove(&path);
+                }
+                Task::none()
+            }
+            Message::SelectAllVisible => {
+                let visible: Vec<_> = self
+                    .visible_results()
+                    .map(|path| path.path.clone())
+                    .collect();
+                self.selected.extend(visible);
+                Task::none()
+            }
+            Message::ClearSelection => {
+                self.selected.clear();
+                Task::none()
+            }
+            Message::CopySelected => {
+                let paths: Vec<_> = self
+                    .paths_over_limit
+                    .iter()
+                    .filter(|path| self.selected.contains(&path.path))
+                    .map(|path| path.path.as_str())
+                    .collect();
+                iced::clipboard::write(paths.join("\n"))
+            }
+            Message::ExportFilteredToggled(enabled) => {
+                self.export_filtered = enabled;
+                Task::none()
+            }
+            Message::ExportSuggestedNamesToggled(enabled) => {
+                self.export_suggested_names = enabled;
+                Task::none()
+            }
+            Message::OpenAfterExportToggled(enabled) => {
+                self.open_after_export = enabled;
+                Task::none()
+            }
+            Message::ExportNameLengthsToggled(enabled) => {
+                self.export_name_lengths = enabled;
+                Task::none()
+            }
+            Message::MetricSelected(metric) => {
+                self.metric = metric;
+                self.update_limit_preview();
+                Task::none()
+            }
+            Message::CountFirstToggled(count_first) => {
+                self.count_first = count_first;
+                Task::none()
+            }
+            Message::DeduplicateToggled(deduplicate) => {
+                self.deduplicate = deduplicate;
+                Task::none()
+            }
+            Message::IncludeRootsToggled(include) => {
+                self.include_roots = include;
+                Task::none()
+            }
+            Message::RecurseToggled(recurse) => {
+                self.recurse = recurse;
+                Task::none()
+            }
+            Message::FollowSymlinksToggled(follow_symlinks) => {
+                self.follow_symlinks = follow_symlinks;
+                Task::none()
+            }
+            Message::ScanArchivesToggled(scan_archives) => {
+                self.scan_archives = scan_archives;
+                Task::none()
+            }
+            Message::SkipHiddenToggled(skip_hidden) => {
+                self.skip_hidden = skip_hidden;
+                Task::none()
+            }
+            Message::NormalizeUnicodeToggled(normalize_unicode) => {
+                self.normalize_unicode = normalize_unicode;
+                Task::none()
+            }
+            Message::MaxDepthChanged(max_depth) => {
+                if max_depth.trim().is_empty() {
+                    self.max_depth = None;
+                } else if let Ok(parsed) = max_depth.trim().parse::<usize>() {
+                    self.max_depth = Some(parsed);
+                }
+                self.max_depth_input = max_depth;
+                Task::none()
+            }
+            Message::MaxDurationChanged(max_duration) => {
+                if max_duration.trim().is_empty() {
+                    self.max_duration = None;
+                } else if let Ok(parsed) = max_duration.trim().parse::<u64>()
+                    && parsed > 0
+                {
+                    self.max_duration = Some(Duration::from_secs(parsed));
+                }
+                self.max_duration_input = max_duration;
+                Task::none()
+            }
+            Message::TimeLimitReached => {
+                if self.can_abort_scan() {
+                    self.time_limited = true;
+                    self.scan_status.cancel();
+                }
+                Task::none()
+            }
+            Message::UpdateIntervalChanged(interval) => {
+                if let Ok(parsed) = interval.trim().parse::<u64>()
+                    && parsed > 0
+                {
+                    self.update_interval = Duration::from_millis(parsed);
+                }
+                self.update_interval_input = interval;
+                Task::none()
+            }
+            Message::RetriesChanged(retries) => {
+                if let Ok(parsed) = retries.trim().parse::<u32>() {
+                    self.retries = parsed;
+                }
+                self.retries_input = retries;
+                Task::none()
+            }
+            Message::ExcludeChanged(exclude) => {
+                self.exclude_patterns = split_patterns(&exclude);
+                self.exclude_input = exclude;
+                Task::none()
+            }
+            Message::IncludeChanged(include) => {
+                self.include_patterns = split_patterns(&include);
+                self.include_input = include;
+                Task::none()
+            }
+            Message::ExtensionsChanged(extensions) => {
+                self.extensions = extensions
+                    .split(',')
+                    .map(scanner::normalize_extension)
+                    .filter(|extension| !extension.is_empty())
+                    .collect();
+                self.extensions_input = extensions;
+                Task::none()
+            }
+            Message::ModifiedSinceChanged(since) => {
+                self.modified_since_input = since;
+                Task::none()
+            }
+            Message::ModifiedUntilChanged(until) => {
+                self.modified_until_input = until;
+                Task::none()
+            }
+            Message::Recheck => {
+                if self.paths_over_limit.is_empty()
+                    || !self.scan_status.is_idle()
+                    || self.rechecking
+                {
+                    Task::none()
+                } else {
+                    let Some(options) = self.scan_options.clone() else {
+                        return Task::none();
+                    };
+                    self.rechecking = true;
+                    let paths = self.paths_over_limit.clone();
+                    Task::future(async move {
+                        let mut remaining = Vec::new();
+                        let mut fixed = Vec::new();
+
+                        for path in paths {
+                            // Entries of an archive aren't on disk, only the archive itself is
+                            let on_disk = path.archive.as_deref().unwrap_or(&path.path);
+                            match fs::symlink_metadata(on_disk).await {
+                                // Renamed, moved or deleted, so it no longer needs fixing
+                                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                                    fixed.push(FixedPath {
+                                        path: path.path,
+                                        before: path.size,
+                                        after: None,
+                                        undo: None,
+                                    });
+                                }
+                                Ok(_) => {
+                                    let after = options.measure(Path::new(&path.path));
+                                    if after > options.limit {
+                                        remaining.push(OverLimit {
+                                            size: after as u64,
+                                            ..path
+                                        });
+                                    } else {
+                                        fixed.push(FixedPath {
+                                            path: path.path,
+                                            before: path.size,
+                                            after: Some(after as u64),
+                                            undo: None,
+                                        });
+                                    }
+                                }
+                                Err(_) => remaining.push(path),
+                            }
+                        }
+
+                        Message::RecheckComplete { remaining, fixed }
+                    })
+                }
+            }
+            Message::RecheckComplete { remaining, fixed } => {
+                self.rechecking = false;
+                self.paths_over_limit = remaining;
+                for path in &fixed {
+                    self.selected.remove(&path.path);
+                }
+                self.fixed_paths.extend(fixed);
+                self.recount_results();
+                self.renaming = None;
+                Task::none()
+            }
+            Message::StartRename(path) => {
+                let new_name = suggest_name(Path::new(&path), self.scan_limit, self.scan_metric)
+                    .or_else(|| {
+                        Path::new(&path)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                    })
+                    .unwrap_or_default();
+                self.renaming = Some(Rename {
+                    path,
+                    new_name,
+                    error: None,
+                    applying: false,
+                });
+                Task::none()
+            }
+            Message::RenameInputChanged(new_name) => {
+                if let Some(rename) = &mut self.renaming {
+                    rename.new_name = new_name;
+                }
+                Task::none()
+            }
+            Message::CancelRename => {
+                self.renaming = None;
+                Task::none()
+            }
+            Message::ConfirmRename => {
+                // Measured the way the scan measured the path
+                let Some(options) = self.scan_options.clone() else {
+                    return Task::none();
+                };
+                let Some(rename) = &mut self.renaming else {
+                    return Task::none();
+                };
+                if rename.applying {
+                    return Task::none();
+                }
+                rename.applying = true;
+                rename.error = None;
+
+                let old_path = PathBuf::from(&rename.path);
+                let new_name = rename.new_name.trim().to_string();
+                Task::future(async move {
+                    Message::RenameComplete(rename_path(old_path, new_name, &options).await)
+                })
+            }
+            Message::SortResults(key) => {
+                self.sort = match self.sort {
+                    Some((current, SortDirection::Ascending)) if current == key => {
+                        Some((key, SortDirection::Descending))
+                    }
+                    _ => Some((key, SortDirection::Ascending)),
+                };
+                self.sort_results();
+                self.refresh_visible();
+                Task::none()
+            }
+            Message::ResultFilterChanged(filter) => {
+                self.result_filter = filter;
+                self.refresh_visible();
+                Task::none()
+            }
+            Message::CaseCollisionsToggled(detect) => {
+                self.detect_case_collisions = detect;
+                Task::none()
+            }
+            Message::TopCountChanged(count) => {
+                if let Ok(parsed) = count.trim().parse::<usize>()
+                    && (1..=MAX_TOP_PATHS).contains(&parsed)
+                {
+                    self.top_paths.rebuild(&self.paths_over_limit, parsed);
+                }
+                self.top_count_input = count;
+                Task::none()
+            }
+            Message::ResultsTabSelected(tab) => {
+                self.results_tab = tab;
+                Task::none()
+            }
+            Message::GroupByParentToggled(group_by_parent) => {
+                self.group_by_parent = group_by_parent;
+                self.group_by_drive = false;
+                self.expanded_groups.clear();
+                Task::none()
+            }
+            Message::GroupByDriveToggled(group_by_drive) => {
+                self.group_by_drive = group_by_drive;
+                self.group_by_parent = false;
+                self.expanded_groups.clear();
+                Task::none()
+            }
+            Message::ResultsScrolled(viewport) => {
+                self.results_viewport = (viewport.absolute_offset().y, viewport.bounds().height);
+                Task::none()
+            }
+            Message::ToggleGroup(group) => {
+                if !self.expanded_groups.remove(&group) {
+                    self.expanded_groups.insert(group);
+                }
+                Task::none()
+            }
+            Message::CopyPath(path) => {
+                self.copied = Some(path.clone());
+                Task::batch([
+                    iced::clipboard::write(path.clone()),
+                    Task::future(async move {
+                        tokio::time::sleep(Duration::from_secs(2)).await;
+                        Message::CopiedExpired(path)
+                    }),
+                ])
+            }
+            Message::CopiedExpired(path) => {
+                if self.copied.as_ref() == Some(&path) {
+                    self.copied = None;
+                }
+                Task::none()
+            }
+            Message::CopySummary => {
+                let Some(summary) = &self.summary else {
+                    return Task::none();
+                };
+                self.summary_copied = true;
+                Task::batch([
+                    iced::clipboard::write(self.summary_text(summary)),
+                    Task::future(async {
+                        tokio::time::sleep(Duration::from_secs(2)).await;
+                        Message::SummaryCopiedExpired
+                    }),
+                ])
+            }
+            Message::SummaryCopiedExpired => {
+                self.summary_copied = false;
+                Task::none()
+            }
+            Message::RevealPath(path) => Task::future(async move {
+                let path = PathBuf::from(path);
+                if let Err(err) = fs::symlink_metadata(&path).await {
+                    return Some(Message::Error(format!(
+                        "Can't open the folder of {}: {}",
+                        path.display(),
+                        err
+                    )));
+                }
+
+                reveal::reveal(&path).err().map(|err| {
+                    Message::Error(format!(
+                        "Failed to open the file manager for {}: {}",
+                        path.display(),
+                        err
+                    ))
+                })
+            })
+            .and_then(Task::done),
+            Message::RenameComplete(result) => {
+                match result {
+                    Ok((old_path, new_path)) => {
+                        self.renaming = None;
+                        self.apply_rename(&old_path, &new_path);
+                    }
+                    Err(err) => {
+                        if let Some(rename) = &mut self.renaming {
+                            rename.applying = false;
+                            rename.error = Some(err);
+                        }
+                    }
+                }
+                Task::none()
+            }
+            Message::UndoFix(index) => {
+                let Some(undo) = self
+                    .fixed_paths
+                    .get(index)
+                    .and_then(|fixed| fixed.undo.clone())
+                else {
+                    return Task::none();
+                };
+                Task::future(async move {
+                    Message::FixUndone(undo_rename(undo.old_path, undo.new_path).await)
+                })
+            }
+            Message::FixUndone(result) => {
+                match result {
+                    Ok((old_path, new_path)) => {
+                        let (undone, fixed) = mem::take(&mut self.fixed_paths)
+                            .into_iter()
+                            .partition(|fixed: &FixedPath| {
+                                fixed.undo.as_ref().is_some_and(|undo| {
+                                    undo.old_path == old_path && undo.new_path == new_path
+                                })
+                            });
+                        self.fixed_paths = fixed;
+                        self.paths_over_limit.extend(
+                            undone
+                                .into_iter()
+                                .filter_map(|fixed| fixed.undo)
+                                .map(|undo| undo.original),
+                        );
+                        // Moves back whatever the rename moved and is still over the limit
+                        self.apply_rename(&new_path, &old_path);
+                    }
+                    Err(err) => self.errors.push_message(io::ErrorKind::Other, err),
+                }
+                Task::none()
+            }
+        }
+    }
+
+    /// Updates the results after `old_path` was renamed to `new_path`.
+    ///
+    /// Entries inside a renamed directory move along with it,
+    /// and everything that is now within the limit is recorded as fixed.
+    fn apply_rename(&mut self, old_path: &str, new_path: &str) {
+        let limit = self.scan_limit as u64;
+        let mut remaining = Vec::with_capacity(self.paths_over_limit.len());
+
+        for path in mem::take(&mut self.paths_over_limit) {
+            let Ok(suffix) = Path::new(&path.path).strip_prefix(old_path) else {
+                remaining.push(path);
+                continue;
+            };
+
+            let renamed = if suffix.as_os_str().is_empty() {
+                PathBuf::from(new_path)
+            } else {
+                Path::new(new_path).join(suffix)
+            };
+            let size = self.measure_path(&renamed);
+            let renamed = renamed.to_string_lossy().to_string();
+            let selected = self.selected.remove(&path.path);
+
+            if size > limit {
+                if selected {
+                    self.selected.insert(renamed.clone());
+                }
+                remaining.push(OverLimit {
+                    path: renamed,
+                    size,
+                    file_bytes: path.file_bytes,
+                    is_dir: path.is_dir,
+                    root: path.root,
+                    archive: path.archive,
+                });
+            } else {
+                self.fixed_paths.push(FixedPath {
+                    path: path.path.clone(),
+                    before: path.size,
+                    after: Some(size),
+                    undo: Some(UndoRename {
+                        old_path: old_path.to_string(),
+                        new_path: new_path.to_string(),
+                        original: path,
+                    }),
+                });
+            }
+        }
+
+        self.paths_over_limit = remaining;
+        self.sort_results();
+        self.recount_results();
+    }
+
+    /// Adds `path` to the totals, the drive and segment counts and the longest paths.
+    fn count_result(&mut self, path: &OverLimit) {
+        self.over_limit_totals.count += 1;
+        self.over_limit_totals.length += path.size;
+        self.over_limit_totals.file_bytes += path.file_bytes;
+        *self
+            .drive_counts
+            .entry(measure::drive(Path::new(&path.path)))
+            .or_default() += 1;
+        self.segments.record(path);
+        self.top_paths.push(path.clone());
+    }
+
+    /// Counts the results again after some were fixed, renamed or brought back.
+    ///
+    /// Dropped results can't be counted again, so the counts from the scan are kept then.
+    fn recount_results(&mut self) {
+        if !self.results_dropped {
+            self.over_limit_totals = OverLimitTotals::default();
+            self.drive_counts.clear();
+            self.segments.clear();
+            self.top_paths.clear();
+            let paths = mem::take(&mut self.paths_over_limit);
+            for path in &paths {
+                self.count_result(path);
+            }
+            self.paths_over_limit = paths;
+        }
+        if let Some(summary) = &self.summary {
+            self.summary = Some(self.summarize(summary.aborted));
+        }
+        self.refresh_visible();
+        self.update_limit_preview();
+        self.update_comparison();
+    }
+
+    /// Applies the selected sort order to the results.
+    ///
+    /// Sorting tens of thousands of results on every update of a scan stalls the window, so new
+    /// results are appended in scan order and sorted once the scan is complete.
+    fn sort_results(&mut self) {
+        let Some((key, direction)) = self.sort else {
+            return;
+        };
+
+        self.paths_over_limit.sort_by(|a, b| {
+            let ordering = match key {
+                SortKey::Path => a.path.cmp(&b.path),
+                SortKey::Length => a.size.cmp(&b.size),
+            };
+            match direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            }
+        });
+    }
+
+    fn can_rename(&self) -> bool {
+        self.scan_status.is_idle()
+            && !self.rechecking
+            && !self.renaming.as_ref().is_some_and(|rename| rename.applying)
+    }
+
+    /// Parses the limit input and updates everything that depends on the limit.
+    fn apply_limit_input(&mut self) {
+        self.limit_edited = None;
+        match parse_limit(&self.limit_input) {
+            Ok(parsed) => {
+                self.limit = parsed;
+                self.limit_error = None;
+            }
+            Err(err) => self.limit_error = Some(err),
+        }
+        self.update_limit_preview();
+    }
+
+    fn update_limit_preview(&mut self) {
+        // Results measured in another unit can't tell anything about the new limit
+        self.limit_preview = if self.scan_status.is_done()
+            && self.metric == self.scan_metric
+            && self.limit != self.scan_limit
+        {
+            // Lower limits need the lengths of the paths under the scan limit too
+            let count = if self.limit >= self.scan_limit {
+                self.paths_over_limit
+                    .iter()
+                    .filter(|path| path.size > self.limit as u64)
+                    .count() as u64
+            } else {
+                self.lengths.count_over(self.limit)
+            };
+            Some(LimitPreview {
+                limit: self.limit,
+                count,
+            })
+        } else {
+            None
+        };
+    }
+
+    pub fn view(&self) -> iced::Element<'_, Message> {
+        use iced::widget::{column, *};
+
+        let main_controls = column![
+            row![
+                button(text("Select Folder")).on_press_maybe(if self.selecting.is_some() {
+                    None
+                } else {
+                    Some(Message::SelectFolder)
+                }),
+                button(text("Add Folder")).on_press_maybe(if self.selecting.is_some() {
+                    None
+                } else {
+                    Some(Message::AddFolder)
+                }),
+                self.selecting
+                    .is_some()
+                    .then(|| button(text("Cancel")).on_press(Message::CancelSelection)),
+                self.recent_folders.is_empty().not().then(|| {
+                    pick_list(
+                        self.recent_folders
+                            .iter()
+                            .cloned()
+                            .map(RecentFolder)
+                            .collect::<Vec<_>>(),
+                        None::<RecentFolder>,
+                        Message::RecentFolderSelected,
+                    )
+                    .placeholder("Recent Folders")
+                }),
+                space::horizontal(),
+                button(text(match self.theme {
+                    Theme::Dark => "Light Mode",
+                    _ => "Dark Mode",
+                }))
+                .on_press(Message::ToggleTheme),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text_input("Or type a folder path", &self.path_input)
+                    .on_input(Message::PathInputChanged)
+                    .on_submit(Message::UsePath),
+                button(text("Use This Path")).on_press_maybe(
+                    (!self.path_input.trim().is_empty()).then_some(Message::UsePath)
+                ),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            self.path_error
+                .as_ref()
+                .map(|error| text(error).size(14).style(text::danger)),
+            column(self.roots.iter().map(|root| {
+                row![
+                    text(root.to_string_lossy()),
+                    button(text("Remove"))
+                        .on_press_maybe(
+                            (!self.scan_status.is_scanning())
+                                .then(|| Message::RemoveFolder(root.clone()))
+                        )
+                        .padding(0)
+                        .style(button::text),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into()
+            }))
+            .spacing(5),
+            row![
+                text("Path Length Limit:"),
+                text_input("", &self.limit_input)
+                    .on_input(Message::LimitChanged)
+                    .on_submit(Message::StartScan)
+                    .style(|theme: &Theme, status| {
+                        let mut style = text_input::default(theme, status);
+                        if self.limit_error.is_some() {
+                            style.border.color = theme.palette().danger;
+                        }
+                        style
+                    })
+                    .width(Length::Fixed(100.0)),
+                text("Error Limit:"),
+                text_input("None", &self.error_limit_input)
+                    .on_input(Message::ErrorLimitChanged)
+                    .width(Length::Fixed(100.0)),
+                text("Count in:"),
+                pick_list(
+                    LengthMetric::ALL,
+                    Some(self.metric),
+                    Message::MetricSelected
+                ),
+                checkbox("Normalize Unicode (NFC)", self.normalize_unicode)
+                    .on_toggle(Message::NormalizeUnicodeToggled),
+                text("Show only paths over:"),
+                text_input("Scan limit", &self.display_threshold_input)
+                    .on_input(Message::DisplayThresholdChanged)
+                    .width(Length::Fixed(100.0)),
+                self.limit_preview.as_ref().map(|preview| {
+                    text(format!(
+                        "{} paths would be over {} (based on the last scan)",
+                        preview.count, preview.limit
+                    ))
+                }),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            self.limit_error
+                .as_ref()
+                .map(|error| text(error).size(14).style(text::danger)),
+            self.limit_from_project.then(|| {
+                text(format!(
+                    "The limit was set by the {} file in the selected folder",
+                    project::LIMIT_FILE
+                ))
+                .size(14)
+            }),
+            row![
+                checkbox("Recurse into subdirectories", self.recurse)
+                    .on_toggle(Message::RecurseToggled),
+                checkbox("Follow symbolic links", self.follow_symlinks)
+                    .on_toggle(Message::FollowSymlinksToggled),
+                checkbox("Skip hidden files", self.skip_hidden)
+                    .on_toggle(Message::SkipHiddenToggled),
+                cfg!(feature = "zip").then(|| {
+                    checkbox("Check inside zip archives", self.scan_archives)
+                        .on_toggle(Message::ScanArchivesToggled)
+                }),
+                checkbox("Check selected folders too", self.include_roots)
+                    .on_toggle(Message::IncludeRootsToggled),
+                checkbox("Count duplicate paths once", self.deduplicate)
+                    .on_toggle(Message::DeduplicateToggled),
+                checkbox("Count entries first for exact progress", self.count_first)
+                    .on_toggle(Message::CountFirstToggled),
+                checkbox(
+                    "Find names differing only by case",
+                    self.detect_case_collisions
+                )
+                .on_toggle(Message::CaseCollisionsToggled),
+                text("Max Depth:"),
+                text_input("Unlimited", &self.max_depth_input)
+                    .on_input_maybe(self.recurse.then_some(Message::MaxDepthChanged))
+                    .width(Length::Fixed(100.0)),
+                text("Exclude Folders:"),
+                text_input("node_modules, target, .git", &self.exclude_input)
+                    .on_input(Message::ExcludeChanged),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Only Report Matching:"),
+                text_input("Everything, e.g. **/assets/**", &self.include_input)
+                    .on_input(Message::IncludeChanged),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Only Report Extensions:"),
+                text_input(
+                    "All files and folders, e.g. png, jpg",
+                    &self.extensions_input
+                )
+                .on_input(Message::ExtensionsChanged),
+                text("Modified From:"),
+                text_input("YYYY-MM-DD", &self.modified_since_input)
+                    .on_input(Message::ModifiedSinceChanged)
+                    .width(Length::Fixed(110.0)),
+                text("To:"),
+                text_input("YYYY-MM-DD", &self.modified_until_input)
+                    .on_input(Message::ModifiedUntilChanged)
+                    .width(Length::Fixed(110.0)),
+                text("Update Every (ms):"),
+                text_input("100", &self.update_interval_input)
+                    .on_input(Message::UpdateIntervalChanged)
+                    .width(Length::Fixed(100.0)),
+                text("Retries:"),
+                text_input("2", &self.retries_input)
+                    .on_input(Message::RetriesChanged)
+                    .width(Length::Fixed(60.0)),
+                text("Stop After (s):"),
+                text_input("Unlimited", &self.max_duration_input)
+                    .on_input(Message::MaxDurationChanged)
+                    .width(Length::Fixed(100.0)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text("Write results while scanning to:"),
+                text(
+                    self.live_csv
+                        .as_ref()
+                        .map_or("No file".to_string(), |path| path.display().to_string())
+                ),
+                button(text("Choose...")).on_press_maybe(
+                    self.scan_status
+                        .is_scanning()
+                        .not()
+                        .then_some(Message::SelectLiveCsv)
+                ),
+                button(text("Clear")).on_press_maybe(
+                    (self.live_csv.is_some() && !self.scan_status.is_scanning())
+                        .then_some(Message::ClearLiveCsv)
+                ),
+                text("Keep at most:"),
+                text_input("All results", &self.keep_results_input)
+                    .on_input_maybe(
+                        self.scan_status
+                            .is_scanning()
+                            .not()
+                            .then_some(Message::KeepResultsChanged)
+                    )
+                    .width(Length::Fixed(100.0)),
+                text("results in memory"),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                button(text("Start Scan"))
+                    .on_press_maybe(self.can_start_scan().then_some(Message::StartScan)),
+                // A running scan can be paused, and only stopped for good once it's paused
+                if !self.scan_status.is_scanning() {
+                    iced::Element::from(button(text("Abort")))
+                } else if self.is_paused() {
+                    row![
+                        button(text("Resume")).on_press(Message::ResumeScan),
+                        button(text("Abort"))
+                            .on_press_maybe(self.can_abort_scan().then_some(Message::AbortScan)),
+                    ]
+                    .spacing(10)
+                    .into()
+                } else {
+                    button(text("Pause"))
+                        .on_press_maybe(self.can_abort_scan().then_some(Message::PauseScan))
+                        .into()
+                },
+                button(text("Export CSV"))
+                    .on_press_maybe(self.can_export().then_some(Message::ExportCsv)),
+                button(text("Export JSON"))
+                    .on_press_maybe(self.can_export().then_some(Message::ExportJson)),
+                button(text("Export Markdown"))
+                    .on_press_maybe(self.can_export().then_some(Message::ExportMarkdown)),
+                self.exporting.then(|| {
+                    button(text("Abort Export")).on_press_maybe(
+                        self.export_token
+                            .as_ref()
+                            .is_some_and(|token| !token.is_cancelled())
+                            .then_some(Message::AbortExport),
+                    )
+                }),
+                button(text("Recheck")).on_press_maybe(
+                    if !self.paths_over_limit.is_empty()
+                        && !self.rechecking
                         && self.scan_status.is_done()
                     {
-                        Some(Message::ExportCsv)
+                        Some(Message::Recheck)
                     } else {
                         None
                     }
                 ),
+                button(text("Rescan")).on_press_maybe(self.can_rescan().then_some(Message::Rescan)),
+                button(text("Compare with Previous..."))
+                    .on_press_maybe(self.scan_status.is_done().then_some(Message::LoadBaseline)),
+                button(text("Clear")).on_press_maybe(
+                    (self.scan_status.is_done() && !self.rechecking)
+                        .then_some(Message::ClearResults)
+                ),
             ]
             .spacing(10),
+            checkbox(
+                "Include suggested file names in export",
+                self.export_suggested_names
+            )
+            .on_toggle(Message::ExportSuggestedNamesToggled),
+            checkbox(
+                "Include base name and extension lengths in export",
+                self.export_name_lengths
+            )
+            .on_toggle(Message::ExportNameLengthsToggled),
+            checkbox("Export filtered view only", self.export_filtered)
+                .on_toggle(Message::ExportFilteredToggled),
+            checkbox("Export selected results only", self.export_selected)
+                .on_toggle(Message::ExportSelectedToggled),
+            checkbox("Open reports after exporting", self.open_after_export)
+                .on_toggle(Message::OpenAfterExportToggled),
+            row![
+                text("CSV Delimiter:"),
+                pick_list(
+                    Delimiter::ALL,
+                    Some(self.delimiter),
+                    Message::DelimiterSelected
+                ),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
         ]
         .spacing(10);
 
-        column![
-            main_controls,
-            match &self.scan_status {
-                ScanStatus::Scanning(_) => {
-                    Some(text(format!("Scanning... {} paths checked", self.scanned)).size(16))
+        column![
+            main_controls,
+            self.disk_space_view(),
+            self.aborted_banner(),
+            self.summary_view(),
+            self.comparison_view(),
+            self.results_view(),
+            self.fixed_paths_view(),
+            self.export_message.as_ref().map(|message| {
+                if self.export_success {
+                    text(message).size(16).style(text::success)
+                } else {
+                    text(message).size(16).style(text::danger)
+                }
+            }),
+            self.errors.is_empty().not().then(|| self.errors_view()),
+            space::vertical(),
+            self.scan_status
+                .is_scanning()
+                .then(|| progress_bar(0.0..=1.0, self.progress())),
+            self.ratio_view(),
+            text(self.status_line()).size(FONT_SIZE),
+            self.scan_status
+                .is_scanning()
+                .then_some(self.current_directory.as_ref())
+                .flatten()
+                .map(|directory| text(format!("Scanning: {}", directory)).size(FONT_SIZE)),
+            self.scan_status
+                .is_scanning()
+                .then_some(self.longest.as_ref())
+                .flatten()
+                .map(|longest| {
+                    text(format!(
+                        "Longest so far ({}): {}",
+                        longest.size, longest.path
+                    ))
+                    .size(FONT_SIZE)
+                }),
+            rule::horizontal(1),
+            footer(),
+        ]
+        .spacing(20)
+        .padding(20)
+        .into()
+    }
+
+    /// Lists how often each kind of error occurred above the kept messages.
+    fn errors_view(&self) -> iced::Element<'_, Message> {
+        use iced::widget::{column, *};
+
+        let kinds = self
+            .errors
+            .kinds()
+            .into_iter()
+            .map(|(kind, count)| format!("{}: {}", kind, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        column![
+            row![
+                text(format!("Errors ({})", self.errors.total()))
+                    .size(18)
+                    .style(text::danger),
+                button(text("Export Errors"))
+                    .on_press_maybe(self.exporting.not().then_some(Message::ExportErrors)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text(kinds).size(14),
+            (self.errors.dropped() > 0).then(|| {
+                text(format!(
+                    "Only the first {} messages are kept",
+                    ErrorLog::MAX_MESSAGES
+                ))
+                .size(14)
+            }),
+            scrollable(column(self.errors.errors().iter().map(|error| {
+                row![
+                    text(error.to_string()).width(Length::Fill),
+                    error.path.as_ref().map(|path| {
+                        button(text("Open Folder").size(14))
+                            .on_press(Message::RevealPath(path.to_string_lossy().to_string()))
+                            .style(button::secondary)
+                    }),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into()
+            })))
+            .height(Length::Fill)
+            .width(Length::Fill)
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// Forgets everything found by the last scan, keeping the folders and settings.
+    fn clear_results(&mut self) {
+        self.paths_over_limit.clear();
+        self.visible.clear();
+        self.case_collisions.clear();
+        self.top_paths.clear();
+        self.over_limit_totals = OverLimitTotals::default();
+        self.drive_counts.clear();
+        self.segments.clear();
+        self.disk_space.clear();
+        self.results_dropped = false;
+        self.expanded_groups.clear();
+        self.selected.clear();
+        self.results_viewport.0 = 0.0;
+        self.errors.clear();
+        self.scanned = 0;
+        self.files_scanned = 0;
+        self.directories_scanned = 0;
+        self.deepest = 0;
+        self.current_directory = None;
+        self.longest = None;
+        self.seen_paths.clear();
+        self.duplicates = 0;
+        self.export_message = None;
+        self.limit_preview = None;
+        self.renaming = None;
+        self.scan_started = None;
+        self.scan_elapsed = None;
+        self.remaining = None;
+        self.summary = None;
+        self.time_limited = false;
+        self.comparison = None;
+        self.lengths = LengthHistogram::default();
+        self.precount = None;
+        self.phase = None;
+    }
+
+    /// Checks that `path` is a folder and scans it next, see [`Message::PathChecked`].
+    ///
+    /// With `file_chooses_folder` a file chooses the folder it's in, like a dropped file does.
+    fn choose_folder(&self, path: PathBuf, file_chooses_folder: bool) -> Task<Message> {
+        // The scan keeps its folders until it's done
+        if self.scan_status.is_scanning() {
+            return Task::none();
+        }
+        Task::future(async move {
+            Message::PathChecked(match fs::metadata(&path).await {
+                Ok(metadata) if metadata.is_dir() => Ok(path),
+                Ok(_) if file_chooses_folder => path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .ok_or_else(|| format!("{} is not in a folder", path.display())),
+                Ok(_) => Err(format!("{} is not a folder", path.display())),
+                Err(err) => Err(format!("Can't open {}: {}", path.display(), err)),
+            })
+        })
+    }
+
+    /// Replaces the folders to scan with `root`.
+    fn select_root(&mut self, root: PathBuf) -> Task<Message> {
+        self.roots = vec![root.clone()];
+        self.scan_status = ScanStatus::WaitingForStart;
+        self.limit_preview = None;
+        load_project_limit(root)
+    }
+
+    /// Opens the folder dialog, its result is sent through `on_picked`.
+    ///
+    /// The dialog can be abandoned with [`Message::CancelSelection`], in case it never returns.
+    fn pick_folder(&mut self, on_picked: fn(Option<Arc<FileHandle>>) -> Message) -> Task<Message> {
+        let (task, handle) = Task::future(async move {
+            let folder = AsyncFileDialog::new().pick_folder().await;
+            on_picked(folder.map(Arc::new))
+        })
+        .abortable();
+        self.selecting = Some(handle);
+        task
+    }
+
+    fn is_paused(&self) -> bool {
+        self.scan_paused
+            .as_ref()
+            .is_some_and(|paused| *paused.borrow())
+    }
+
+    /// Summarizes what the app is currently doing in a single line.
+    fn status_line(&self) -> String {
+        let state = if self.selecting.is_some() {
+            "Selecting folder...".to_string()
+        } else if self.exporting {
+            "Exporting report...".to_string()
+        } else if self.rechecking {
+            "Rechecking paths...".to_string()
+        } else {
+            match &self.scan_status {
+                ScanStatus::WaitingForStart => "Idle".to_string(),
+                ScanStatus::Scanning(token) if token.is_cancelled() => {
+                    format!("Aborting... {} paths checked", format_count(self.scanned))
+                }
+                ScanStatus::Scanning(_) if self.phase == Some(Phase::Counting) => {
+                    "Counting entries (phase 1 of 2)...".to_string()
+                }
+                ScanStatus::Scanning(_) if self.is_paused() => {
+                    format!("Paused, {} paths checked", format_count(self.scanned))
+                }
+                ScanStatus::Scanning(_) => {
+                    let mut state = format!(
+                        "{} {} paths checked{} ({})",
+                        if self.phase == Some(Phase::Measuring) {
+                            format!(
+                                "Measuring (phase 2 of 2), {:.0}% done...",
+                                self.progress() * 100.0
+                            )
+                        } else {
+                            "Scanning...".to_string()
+                        },
+                        format_count(self.scanned),
+                        self.scan_rate(),
+                        self.entry_kinds()
+                    );
+                    if let Some(remaining) = self.remaining {
+                        state.push_str(&format!(", ~{} remaining", format_duration(remaining)));
+                    }
+                    if let Some(bytes) = self.memory_usage {
+                        state.push_str(&format!(", using {} of memory", format_bytes(bytes)));
+                    }
+                    state
+                }
+                ScanStatus::Done => format!(
+                    "Scan finished: {} paths checked{} ({}), {} over limit",
+                    format_count(self.scanned),
+                    self.scan_rate(),
+                    self.entry_kinds(),
+                    format_count(self.paths_over_limit.len() as u64)
+                ),
+                ScanStatus::Aborted => format!(
+                    "Scan aborted: {} paths checked{} ({}), {} over limit so far",
+                    format_count(self.scanned),
+                    self.scan_rate(),
+                    self.entry_kinds(),
+                    format_count(self.paths_over_limit.len() as u64)
+                ),
+            }
+        };
+
+        let limit = if self.scan_status.is_scanning() || self.scan_status.is_done() {
+            self.scan_limit
+        } else {
+            self.limit
+        };
+
+        match self.roots.as_slice() {
+            [] => format!("{} | No folder selected | Limit: {}", state, limit),
+            [root] => format!("{} | Folder: {} | Limit: {}", state, root.display(), limit),
+            roots => format!("{} | {} folders | Limit: {}", state, roots.len(), limit),
+        }
+    }
+
+    /// Returns the progress of the running scan between 0 and 1.
+    ///
+    /// Until the pre-count is known, the value sweeps back and forth to show that work is ongoing.
+    fn progress(&self) -> f32 {
+        match self.precount {
+            Some(total) if total > 0 => (self.scanned as f32 / total as f32).min(1.0),
+            _ => {
+                let seconds = self
+                    .scan_started
+                    .map(|started| started.elapsed().as_secs_f32())
+                    .unwrap_or_default();
+                let phase = seconds % 2.0;
+                if phase < 1.0 { phase } else { 2.0 - phase }
+            }
+        }
+    }
+
+    /// Formats how many files and folders were scanned, e.g. `10,000 files, 1,200 folders`.
+    fn entry_kinds(&self) -> String {
+        format!(
+            "{} files, {} folders",
+            format_count(self.files_scanned),
+            format_count(self.directories_scanned)
+        )
+    }
+
+    /// Formats the scan throughput, e.g. ` (4,200/s)`.
+    ///
+    /// Uses the final average once the scan is complete and stays empty
+    /// until enough time has passed for a meaningful rate.
+    fn scan_rate(&self) -> String {
+        let elapsed = self
+            .scan_elapsed
+            .or_else(|| self.scan_started.map(|started| started.elapsed()));
+
+        match elapsed {
+            Some(elapsed) if elapsed >= Duration::from_millis(100) => format!(
+                " ({}/s)",
+                format_count((self.scanned as f64 / elapsed.as_secs_f64()) as u64)
+            ),
+            _ => String::new(),
+        }
+    }
+
+    fn summarize(&self, aborted: bool) -> ScanSummary {
+        // The totals also cover results which were dropped to save memory
+        let totals = self.over_limit_totals;
+
+        ScanSummary {
+            scanned: self.scanned,
+            over_limit: totals.count,
+            longest: self
+                .top_paths
+                .sorted()
+                .into_iter()
+                .take(SUMMARY_LONGEST)
+                .cloned()
+                .collect(),
+            average_over_limit: if totals.count == 0 {
+                0.0
+            } else {
+                totals.length as f64 / totals.count as f64
+            },
+            file_bytes: totals.file_bytes,
+            drives: self
+                .drive_counts
+                .iter()
+                .map(|(drive, count)| (drive.clone(), *count))
+                .collect(),
+            common_segments: self
+                .segments
+                .top(SegmentTally::DEFAULT_COUNT, self.scan_metric),
+            deepest: self.deepest,
+            duplicates: self.duplicates,
+            elapsed: self.scan_elapsed.unwrap_or_default(),
+            time_limited: self.time_limited,
+            aborted,
+        }
+    }
+
+    /// Describes the results in a few lines of plain text, to paste into a chat or a ticket.
+    fn summary_text(&self, summary: &ScanSummary) -> String {
+        let roots: Vec<_> = self
+            .scan_roots
+            .iter()
+            .map(|root| root.to_string_lossy())
+            .collect();
+        let mut lines = vec![
+            format!("Scanned: {}", roots.join(", ")),
+            format!("Limit: {} ({})", self.scan_limit, self.scan_metric),
+            format!(
+                "{} paths scanned, {} over limit ({:.1}%)",
+                format_count(summary.scanned),
+                format_count(summary.over_limit),
+                summary.percentage_over_limit()
+            ),
+        ];
+        if summary.aborted {
+            lines.push("The scan was aborted, the results are partial".to_string());
+        }
+        if !summary.longest.is_empty() {
+            lines.push("Longest paths:".to_string());
+            lines.extend(
+                summary
+                    .longest
+                    .iter()
+                    .map(|path| format!("  {} ({})", path.path, path.size)),
+            );
+        }
+        lines.join("\n")
+    }
+
+    /// Warns that an aborted scan's results are partial, so they aren't taken for a full audit.
+    fn aborted_banner(&self) -> Option<iced::Element<'_, Message>> {
+        use iced::widget::*;
+
+        if !self.scan_status.is_aborted() {
+            return None;
+        }
+        let reason = if self.time_limited {
+            "Scan stopped at the time limit"
+        } else {
+            "Scan aborted"
+        };
+        Some(
+            text(format!(
+                "{} \u{2014} results are partial, not every folder was checked",
+                reason
+            ))
+            .size(18)
+            .style(text::warning)
+            .into(),
+        )
+    }
+
+    fn summary_view(&self) -> Option<iced::Element<'_, Message>> {
+        use iced::widget::{column, *};
+
+        let summary = self.summary.as_ref()?;
+
+        Some(
+            column![
+                row![
+                    text("Summary").size(18),
+                    button(text(if self.summary_copied {
+                        "Copied!"
+                    } else {
+                        "Copy Summary"
+                    }))
+                    .on_press(Message::CopySummary)
+                    .style(button::secondary),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+                text(if summary.time_limited {
+                    format!(
+                        "Stopped at the time limit after {}, the results are partial",
+                        format_duration(summary.elapsed)
+                    )
+                } else if summary.aborted {
+                    format!("Aborted after {}", format_duration(summary.elapsed))
+                } else {
+                    format!("Completed in {}", format_duration(summary.elapsed))
+                }),
+                text(format!(
+                    "{} paths scanned, {} over limit ({:.1}%)",
+                    format_count(summary.scanned),
+                    format_count(summary.over_limit),
+                    summary.percentage_over_limit()
+                )),
+                (summary.duplicates > 0).then(|| {
+                    text(format!(
+                        "{} duplicate paths were only counted once",
+                        format_count(summary.duplicates)
+                    ))
+                }),
+                text(format!(
+                    "Deepest nesting: {} {}",
+                    summary.deepest,
+                    if summary.deepest == 1 {
+                        "level"
+                    } else {
+                        "levels"
+                    }
+                )),
+                summary.longest.first().map(|longest| {
+                    text(format!("Longest path ({}): {}", longest.size, longest.path))
+                }),
+                (summary.over_limit > 0).then(|| {
+                    text(format!(
+                        "Average length over limit: {:.1}",
+                        summary.average_over_limit
+                    ))
+                }),
+                (summary.over_limit > 0).then(|| {
+                    text(format!(
+                        "Size of the files over limit: {}",
+                        format_bytes(summary.file_bytes)
+                    ))
+                }),
+                summary.common_segments.is_empty().not().then(|| {
+                    column![text("Common culprits:")]
+                        .extend(summary.common_segments.iter().map(|segment| {
+                            text(format!(
+                                "{} ({} long) in {} paths",
+                                segment.name,
+                                segment.length,
+                                format_count(segment.count)
+                            ))
+                            .size(14)
+                            .into()
+                        }))
+                        .spacing(2)
+                }),
+                (summary.drives.len() > 1).then(|| {
+                    text(format!(
+                        "Over limit per drive: {}",
+                        summary
+                            .drives
+                            .iter()
+                            .map(|(drive, count)| format!("{} {}", drive, format_count(*count)))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                }),
+                row![
+                    text("What if the limit were:"),
+                    slider(
+                        1..=self
+                            .lengths
+                            .max_length()
+                            .max(self.scan_limit)
+                            .clamp(1, LengthHistogram::MAX_LENGTH)
+                            as u32,
+                        self.what_if_limit,
+                        Message::WhatIfLimitChanged
+                    )
+                    .width(Length::Fixed(300.0)),
+                    text(format!(
+                        "{}: {} paths over limit",
+                        self.what_if_limit,
+                        format_count(self.lengths.count_over(self.what_if_limit as usize))
+                    )),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+            ]
+            .spacing(5)
+            .into(),
+        )
+    }
+
+    /// Marks an export as running and returns the token to abort it with.
+    fn start_export(&mut self) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.exporting = true;
+        self.export_message = None;
+        self.export_token = Some(token.clone());
+        token
+    }
+
+    /// Shows how many of the scanned paths are over the limit as a bar split in two.
+    fn ratio_view(&self) -> Option<iced::Element<'_, Message>> {
+        use iced::widget::{column, *};
+
+        if self.scanned == 0 {
+            return None;
+        }
+        // The totals also cover results which were dropped to save memory
+        let over_limit = self.over_limit_totals.count.min(self.scanned);
+        let ratio = over_limit as f64 / self.scanned as f64;
+        // Even a single path over the limit should be visible
+        let over_portion = if over_limit == 0 {
+            0
+        } else {
+            ((ratio * RATIO_PORTIONS as f64) as u16).max(1)
+        };
+        let ok_portion = RATIO_PORTIONS - over_portion;
+
+        let part = |portion: u16, over_limit: bool| {
+            (portion > 0).then(|| {
+                container(space::horizontal())
+                    .width(Length::FillPortion(portion))
+                    .height(Length::Fixed(8.0))
+                    .style(move |theme: &Theme| {
+                        let palette = theme.extended_palette();
+                        let color = if over_limit {
+                            palette.danger.base.color
+                        } else {
+                            palette.success.base.color
+                        };
+                        container::Style {
+                            background: Some(color.into()),
+                            ..Default::default()
+                        }
+                    })
+            })
+        };
+
+        Some(
+            column![
+                row![part(over_portion, true), part(ok_portion, false)],
+                text(format!(
+                    "{} over limit, {} ok ({:.1}% over limit)",
+                    format_count(over_limit),
+                    format_count(self.scanned - over_limit),
+                    ratio * 100.0
+                ))
+                .size(14),
+            ]
+            .spacing(5)
+            .into(),
+        )
+    }
+
+    fn disk_space_view(&self) -> Option<iced::Element<'_, Message>> {
+        use iced::widget::{column, *};
+
+        self.disk_space.is_empty().not().then(|| {
+            column(self.disk_space.iter().map(|(root, space)| {
+                let line = text(format!(
+                    "Free space at {}: {} of {}{}",
+                    root.display(),
+                    format_bytes(space.free),
+                    format_bytes(space.total),
+                    if space.is_nearly_full() {
+                        ", nearly full"
+                    } else {
+                        ""
+                    }
+                ))
+                .size(14);
+                if space.is_nearly_full() {
+                    line.style(text::warning).into()
+                } else {
+                    line.into()
+                }
+            }))
+            .spacing(2)
+            .into()
+        })
+    }
+
+    fn update_comparison(&mut self) {
+        self.comparison = self
+            .baseline
+            .as_ref()
+            .map(|baseline| baseline.compare(&self.paths_over_limit, &self.scan_roots));
+    }
+
+    fn comparison_view(&self) -> Option<iced::Element<'_, Message>> {
+        use iced::widget::{column, *};
+
+        let baseline = self.baseline.as_ref()?;
+        let comparison = self.comparison.as_ref()?;
+
+        Some(
+            column![
+                row![
+                    text(format!("Compared with {}", baseline.file.display())).size(18),
+                    button(text("Clear")).on_press(Message::ClearBaseline),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+                text(format!(
+                    "{} new, {} unchanged, {} fixed",
+                    format_count(comparison.new),
+                    format_count(comparison.unchanged),
+                    format_count(comparison.fixed.len() as u64)
+                )),
+                self.results_dropped.then(|| {
+                    text("Only the kept results were compared, so some paths may show as fixed")
+                        .size(14)
+                        .style(text::warning)
+                }),
+                comparison.fixed.is_empty().not().then(|| {
+                    scrollable(column(comparison.fixed.iter().map(|(path, length)| {
+                        text(format!("{} -> fixed: {}", length, path))
+                            .size(14)
+                            .style(text::success)
+                            .into()
+                    })))
+                    .height(Length::Fixed(100.0))
+                    .width(Length::Fill)
+                }),
+            ]
+            .spacing(5)
+            .into(),
+        )
+    }
+
+    /// Updates the estimate of the remaining scan time.
+    ///
+    /// The total number of directories isn't known up front, so the estimate assumes the ones
+    /// found but not read yet are all that's left. It's smoothed, as that number jumps around a lot.
+    fn update_remaining(&mut self, directories_read: u64, directories_pending: u64) {
+        if self.scan_status.is_cancelling() {
+            return;
+        }
+        let Some(elapsed) = self.scan_started.map(|started| started.elapsed()) else {
+            return;
+        };
+        if elapsed < Duration::from_secs(1) || directories_read == 0 {
+            return;
+        }
+
+        let per_directory = elapsed.as_secs_f64() / directories_read as f64;
+        let estimate = Duration::from_secs_f64(per_directory * directories_pending as f64);
+
+        self.remaining = Some(match self.remaining {
+            Some(previous) => previous.mul_f64(0.9) + estimate.mul_f64(0.1),
+            None => estimate,
+        });
+    }
+
+    fn can_start_scan(&self) -> bool {
+        !self.roots.is_empty()
+            && !self.scan_status.is_scanning()
+            && !self.rechecking
+            && self.limit_error.is_none()
+    }
+
+    fn can_abort_scan(&self) -> bool {
+        self.scan_status.is_scanning() && !self.scan_status.is_cancelling()
+    }
+
+    /// Whether the last scan can be run again with the same parameters.
+    fn can_rescan(&self) -> bool {
+        self.scan_options.is_some() && !self.scan_status.is_scanning() && !self.rechecking
+    }
+
+    fn can_export(&self) -> bool {
+        self.exported_results().next().is_some() && !self.exporting && self.scan_status.is_done()
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        // Without a scan running nothing changes over time, so the app can stay asleep
+        let ticks = if self.scan_status.is_scanning() && !self.is_paused() {
+            iced::time::every(TICK_INTERVAL).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        // Keys already handled by a widget, like typing into a text input, are not shortcuts
+        let events = event::listen_with(|event, status, id| match (event, status) {
+            (
+                Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }),
+                event::Status::Ignored,
+            ) => match key.as_ref() {
+                keyboard::Key::Character("r") if modifiers.command() => {
+                    Some(Message::Shortcut(Shortcut::StartScan))
+                }
+                keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                    Some(Message::Shortcut(Shortcut::AbortScan))
                 }
-                ScanStatus::Done => {
-                    Some(text(format!("Scan Finished! {} paths checked", self.scanned)).size(16))
+                keyboard::Key::Character("e") if modifiers.command() => {
+                    Some(Message::Shortcut(Shortcut::ExportCsv))
                 }
-                ScanStatus::WaitingForStart => None,
+                _ => None,
             },
-            if self.scan_status.is_idle() {
-                None
-            } else if self.paths_over_limit.is_empty() {
-                Some(text("No paths over limit found"))
+            (Event::Window(window::Event::FileDropped(path)), _) => {
+                Some(Message::FileDropped(path))
+            }
+            (Event::Window(window::Event::Moved(position)), _) => {
+                Some(Message::WindowMoved(position))
+            }
+            (Event::Window(window::Event::Resized(size)), _) => Some(Message::WindowResized(size)),
+            (Event::Window(window::Event::CloseRequested), _) => {
+                Some(Message::WindowCloseRequested(id))
+            }
+            _ => None,
+        });
+
+        Subscription::batch([events, ticks])
+    }
+
+    /// Shows the outcome of an export and allows the next one.
+    fn finish_export(&mut self, result: Result<String, String>) {
+        self.exporting = false;
+        self.export_token = None;
+        match result {
+            Ok(success_msg) => {
+                self.export_message = Some(success_msg);
+                self.export_success = true;
+            }
+            Err(error_msg) => {
+                self.export_message = Some(error_msg);
+                self.export_success = false;
+            }
+        }
+    }
+
+    /// Returns the results over the display threshold, in display order.
+    ///
+    /// These are what gets exported, regardless of the text filter.
+    fn shown_results(&self) -> impl Iterator<Item = &OverLimit> {
+        let threshold = self.display_threshold.unwrap_or(0) as u64;
+        self.paths_over_limit
+            .iter()
+            .filter(move |path| path.size > threshold)
+    }
+
+    /// Returns the results which are over the display threshold and match the current filter,
+    /// in display order.
+    fn visible_results(&self) -> impl Iterator<Item = &OverLimit> {
+        self.visible
+            .iter()
+            .map(|&index| &self.paths_over_limit[index])
+    }
+
+    /// Finds the visible results again, after the results, the filter or the threshold changed.
+    fn refresh_visible(&mut self) {
+        self.visible.clear();
+        self.extend_visible(0);
+    }
+
+    /// Adds the results from `first` on to the visible ones, if they're shown and match the
+    /// filter. Results before `first` have to be unchanged.
+    fn extend_visible(&mut self, first: usize) {
+        let threshold = self.display_threshold.unwrap_or(0) as u64;
+        let filter = self.result_filter.to_lowercase();
+        let matching = self.paths_over_limit[first..]
+            .iter()
+            .enumerate()
+            .filter(|(_, path)| {
+                path.size > threshold
+                    && (filter.is_empty() || path.path.to_lowercase().contains(&filter))
+            })
+            .map(|(index, _)| first + index);
+        self.visible.extend(matching);
+    }
+
+    /// Builds the report of the JSON and Markdown exports from the exported results.
+    fn json_report(&self) -> JsonReport {
+        JsonReport {
+            roots: self
+                .scan_roots
+                .iter()
+                .map(|root| root.to_string_lossy().to_string())
+                .collect(),
+            limit: self.scan_limit,
+            error_limit: self.error_limit,
+            metric: self.scan_metric.to_string(),
+            extensions: self.scan_extensions.clone(),
+            modified_since: self
+                .scan_options
+                .as_ref()
+                .and_then(|options| options.modified_since)
+                .map(date::format_date),
+            modified_until: self
+                .scan_options
+                .as_ref()
+                .and_then(|options| options.modified_until)
+                .map(date::format_date),
+            relative_paths: self.relative_paths,
+            deepest_nesting: self.deepest,
+            duration_seconds: self
+                .summary
+                .as_ref()
+                .map(|summary| summary.elapsed.as_secs_f64()),
+            aborted: self.scan_status.is_aborted(),
+            timestamp: export::unix_timestamp(),
+            common_segments: self
+                .segments
+                .top(SegmentTally::DEFAULT_COUNT, self.scan_metric)
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            paths: self
+                .exported_results()
+                .map(|path| JsonPath {
+                    path: self.displayed_path(path).to_string(),
+                    length: self.displayed_length(path),
+                    root: path.root.to_string(),
+                    severity: path
+                        .severity(self.error_limit)
+                        .map(|severity| severity.as_str().to_string()),
+                    archive: path.archive.as_deref().map(str::to_string),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the results an export writes, only the visible ones if the filtered view is exported.
+    fn exported_results(&self) -> Box<dyn Iterator<Item = &OverLimit> + '_> {
+        if self.export_selected {
+            Box::new(
+                self.paths_over_limit
+                    .iter()
+                    .filter(|path| self.selected.contains(&path.path)),
+            )
+        } else if self.export_filtered {
+            Box::new(self.visible_results())
+        } else {
+            Box::new(self.shown_results())
+        }
+    }
+
+    /// Groups the visible results by their parent directory, or by their drive.
+    ///
+    /// Groups with the most results come first, results keep their display order within a group.
+    fn grouped_results(&self) -> Vec<ResultGroup<'_>> {
+        let mut groups: HashMap<String, Vec<&OverLimit>> = HashMap::new();
+        for path in self.visible_results() {
+            let name = if self.group_by_drive {
+                measure::drive(Path::new(&path.path))
             } else {
-                Some(
+                Path::new(&path.path)
+                    .parent()
+                    .map(|parent| parent.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            };
+            groups.entry(name).or_default().push(path);
+        }
+
+        let mut groups: Vec<_> = groups
+            .into_iter()
+            .filter_map(|(name, paths)| {
+                let longest = *paths.iter().max_by_key(|path| path.size)?;
+                Some(ResultGroup {
+                    name,
+                    paths,
+                    longest,
+                })
+            })
+            .collect();
+        groups.sort_by(|a, b| {
+            b.paths
+                .len()
+                .cmp(&a.paths.len())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        groups
+    }
+
+    fn group_view<'a>(&'a self, group: ResultGroup<'a>) -> iced::Element<'a, Message> {
+        use iced::widget::{column, *};
+
+        let expanded = self.expanded_groups.contains(&group.name);
+
+        column![
+            button(
+                text(format!(
+                    "{} {} ({} paths)",
+                    if expanded { "▼" } else { "▶" },
+                    group.name,
+                    group.paths.len()
+                ))
+                .shaping(text::Shaping::Advanced)
+            )
+            .on_press(Message::ToggleGroup(group.name.clone()))
+            .padding(0)
+            .style(button::text),
+            text(format!(
+                "Longest: {} ({})",
+                group.longest.path, group.longest.size
+            ))
+            .size(14),
+            expanded.then(|| {
+                let hidden = group.paths.len().saturating_sub(MAX_GROUP_ROWS);
+                column(
+                    group
+                        .paths
+                        .into_iter()
+                        .take(MAX_GROUP_ROWS)
+                        .map(|path| self.result_row(path)),
+                )
+                .push((hidden > 0).then(|| {
                     text(format!(
-                        "Found {} paths over limit ({})",
-                        self.paths_over_limit.len(),
-                        self.scan_limit
+                        "{} more paths in this group, export the results to see all of them",
+                        format_count(hidden as u64)
                     ))
-                    .size(18),
-                )
-            },
-            self.exporting.then(|| text("Exporting to CSV...").size(16)),
-            self.export_message.as_ref().map(|message| {
-                if self.export_success {
-                    text(message)
-                        .size(16)
-                        .color(iced::Color::from_rgb(0.0, 0.6, 0.0))
+                    .size(14)
+                }))
+                .padding(iced::Padding::ZERO.left(20))
+            }),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn results_view(&self) -> Option<iced::Element<'_, Message>> {
+        use iced::widget::{column, *};
+
+        match self.scan_status {
+            ScanStatus::WaitingForStart => return None,
+            ScanStatus::Scanning(_) | ScanStatus::Done | ScanStatus::Aborted => (),
+        }
+
+        let tab = |label: String, tab: ResultsTab| {
+            button(text(label))
+                .on_press(Message::ResultsTabSelected(tab))
+                .style(if self.results_tab == tab {
+                    button::primary
                 } else {
-                    text(message)
-                        .size(16)
-                        .color(iced::Color::from_rgb(0.8, 0.2, 0.2))
-                }
+                    button::secondary
+                })
+        };
+        // The collisions tab only makes sense if the scan looked for them at all
+        let collisions = self
+            .scan_options
+            .as_ref()
+            .is_some_and(|options| options.detect_case_collisions);
+        let tabs = row![
+            tab("Over Limit".to_string(), ResultsTab::OverLimit),
+            tab(
+                format!("Top {}", self.top_paths.capacity()),
+                ResultsTab::TopPaths
+            ),
+            collisions.then(|| {
+                tab(
+                    format!("Case Collisions ({})", self.case_collisions.len()),
+                    ResultsTab::CaseCollisions,
+                )
             }),
-            self.errors.is_empty().not().then(|| {
+        ]
+        .spacing(10);
+
+        match self.results_tab {
+            ResultsTab::OverLimit => (),
+            ResultsTab::TopPaths => {
+                return Some(column![tabs, self.top_paths_view()].spacing(5).into());
+            }
+            ResultsTab::CaseCollisions if collisions => {
+                return Some(column![tabs, self.case_collisions_view()].spacing(5).into());
+            }
+            ResultsTab::CaseCollisions => (),
+        }
+
+        if self.results_dropped {
+            return Some(
                 column![
-                    text(format!("Errors ({})", self.errors.len()))
-                        .size(18)
-                        .color(iced::Color::from_rgb(0.8, 0.2, 0.2)),
-                    scrollable(column(self.errors.iter().map(|error| text(error).into())))
-                        .height(Length::Fill)
-                        .width(Length::Fill)
+                    tabs,
+                    text(format!(
+                        "Found {} paths over limit ({}). That's more than the {} kept in \
+                         memory, so only the longest are shown in the top list.",
+                        format_count(self.over_limit_totals.count),
+                        self.scan_limit,
+                        format_count(self.keep_results.unwrap_or_default() as u64)
+                    )),
+                    text(match &self.live_csv {
+                        Some(path) => format!("All of them are written to {}", path.display()),
+                        None => "Choose a file to write results to while scanning to get all \
+                                 of them."
+                            .to_string(),
+                    }),
                 ]
+                .spacing(5)
+                .into(),
+            );
+        }
+
+        if self.paths_over_limit.is_empty() {
+            return Some(
+                column![tabs, text("No paths over limit found")]
+                    .spacing(5)
+                    .into(),
+            );
+        }
+
+        let title = row![
+            text(format!(
+                "Found {} paths over limit ({})",
+                self.paths_over_limit.len(),
+                self.scan_limit
+            ))
+            .size(18),
+            space::horizontal(),
+            (!self.result_filter.is_empty() || self.display_threshold.is_some()).then(|| {
+                text(format!(
+                    "Showing {} of {}",
+                    self.visible.len(),
+                    self.paths_over_limit.len()
+                ))
             }),
-            space::vertical(),
-            rule::horizontal(1),
-            footer(),
+            checkbox("Relative paths", self.relative_paths)
+                .on_toggle(Message::RelativePathsToggled),
+            pick_list(
+                Separators::ALL,
+                Some(self.separators),
+                Message::SeparatorsSelected
+            ),
+            checkbox("Suggest shorter paths", self.suggest_paths)
+                .on_toggle(Message::SuggestPathsToggled),
+            checkbox("Group by folder", self.group_by_parent)
+                .on_toggle(Message::GroupByParentToggled),
+            checkbox("Group by drive", self.group_by_drive).on_toggle(Message::GroupByDriveToggled),
+            text_input("Filter results", &self.result_filter)
+                .on_input(Message::ResultFilterChanged)
+                .width(Length::Fixed(250.0)),
         ]
-        .spacing(20)
-        .padding(20)
+        .spacing(10)
+        .align_y(Vertical::Center);
+
+        Some(
+            column![
+                tabs,
+                title,
+                row![
+                    text(format!(
+                        "{} selected",
+                        format_count(self.selected.len() as u64)
+                    )),
+                    button(text("Select All")).on_press(Message::SelectAllVisible),
+                    button(text("Clear Selection")).on_press_maybe(
+                        self.selected
+                            .is_empty()
+                            .not()
+                            .then_some(Message::ClearSelection)
+                    ),
+                    button(text("Copy Selected")).on_press_maybe(
+                        self.selected
+                            .is_empty()
+                            .not()
+                            .then_some(Message::CopySelected)
+                    ),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center),
+                row![
+                    self.sort_header("Length", SortKey::Length)
+                        .width(Length::Fixed(80.0)),
+                    self.sort_header("Path", SortKey::Path).width(Length::Fill),
+                    self.suggest_paths
+                        .then(|| text("Suggested Path").width(Length::Fill)),
+                ]
+                .spacing(10),
+                scrollable(if self.group_by_parent || self.group_by_drive {
+                    column(
+                        self.grouped_results()
+                            .into_iter()
+                            .map(|group| self.group_view(group)),
+                    )
+                    .spacing(10)
+                } else {
+                    self.windowed_results()
+                })
+                .on_scroll(Message::ResultsScrolled)
+                .height(Length::Fill)
+                .width(Length::Fill)
+            ]
+            .spacing(5)
+            .into(),
+        )
+    }
+
+    /// Lists the longest paths found, no matter the filter or sort order of the full list.
+    fn top_paths_view(&self) -> iced::Element<'_, Message> {
+        use iced::widget::{column, *};
+
+        column![
+            row![
+                text("Show the longest:"),
+                text_input("20", &self.top_count_input)
+                    .on_input(Message::TopCountChanged)
+                    .width(Length::Fixed(100.0)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            scrollable(column(
+                self.top_paths
+                    .sorted()
+                    .into_iter()
+                    .map(|path| self.result_row(path))
+            ))
+            .height(Length::Fill)
+            .width(Length::Fill),
+        ]
+        .spacing(5)
         .into()
     }
 
-    fn start_scan(
+    /// Lists each group of entries whose names only differ by case.
+    fn case_collisions_view(&self) -> iced::Element<'_, Message> {
+        use iced::widget::{column, *};
+
+        if self.case_collisions.is_empty() {
+            return text("No names differing only by case found").into();
+        }
+
+        scrollable(
+            column(self.case_collisions.iter().map(|collision| {
+                column(
+                    collision
+                        .paths
+                        .iter()
+                        .map(|path| text(path).wrapping(text::Wrapping::None).into()),
+                )
+                .spacing(2)
+                .into()
+            }))
+            .spacing(10),
+        )
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into()
+    }
+
+    /// Builds only the result rows in and around the visible part of the list.
+    ///
+    /// Empty space stands in for the rows above and below, so the scrollbar still matches the
+    /// whole list. This keeps scrolling smooth with tens of thousands of results.
+    fn windowed_results(&self) -> iced::widget::Column<'_, Message> {
+        use iced::widget::{column, *};
+
+        let (offset, height) = self.results_viewport;
+        let overscan = RESULT_ROW_HEIGHT * 10.0;
+        let (start, end) = (offset - overscan, offset + height + overscan);
+
+        let mut above = 0.0;
+        let mut below = 0.0;
+        let mut rows = Vec::new();
+        let mut y = 0.0;
+        for path in self.visible_results() {
+            let row_height = self.row_height(path);
+            if y + row_height < start {
+                above += row_height;
+            } else if y > end {
+                below += row_height;
+            } else {
+                rows.push(
+                    container(self.result_row(path))
+                        .height(Length::Fixed(row_height))
+                        .clip(true)
+                        .into(),
+                );
+            }
+            y += row_height;
+        }
+
+        column![space::vertical().height(Length::Fixed(above))]
+            .extend(rows)
+            .push(space::vertical().height(Length::Fixed(below)))
+    }
+
+    fn row_height(&self, path: &OverLimit) -> f32 {
+        match &self.renaming {
+            Some(rename) if rename.path == path.path && rename.error.is_some() => {
+                RESULT_ROW_HEIGHT * 2.0
+            }
+            _ => RESULT_ROW_HEIGHT,
+        }
+    }
+
+    fn sort_header(&self, label: &'static str, key: SortKey) -> iced::widget::Button<'_, Message> {
+        use iced::widget::*;
+
+        let indicator = match self.sort {
+            Some((current, SortDirection::Ascending)) if current == key => " ▲",
+            Some((current, SortDirection::Descending)) if current == key => " ▼",
+            _ => "",
+        };
+
+        button(text(format!("{}{}", label, indicator)).shaping(text::Shaping::Advanced))
+            .on_press(Message::SortResults(key))
+            .padding(0)
+            .style(button::text)
+    }
+
+    fn result_row<'a>(&'a self, path: &'a OverLimit) -> iced::Element<'a, Message> {
+        use iced::widget::{column, *};
+
+        match &self.renaming {
+            Some(rename) if rename.path == path.path => {
+                let input = text_input("New name", &rename.new_name);
+                let input = if rename.applying {
+                    input
+                } else {
+                    input
+                        .on_input(Message::RenameInputChanged)
+                        .on_submit(Message::ConfirmRename)
+                };
+
+                column![
+                    row![
+                        self.severity_text(self.displayed_length(path).to_string(), path)
+                            .width(Length::Fixed(80.0)),
+                        self.severity_text(self.displayed_path(path), path)
+                            .wrapping(text::Wrapping::None)
+                            .width(Length::FillPortion(2)),
+                        input.width(Length::FillPortion(1)),
+                        button(text("Confirm Rename")).on_press_maybe(
+                            rename.applying.not().then_some(Message::ConfirmRename)
+                        ),
+                        button(text("Cancel"))
+                            .on_press_maybe(rename.applying.not().then_some(Message::CancelRename)),
+                    ]
+                    .spacing(10)
+                    .align_y(Vertical::Center),
+                    rename
+                        .error
+                        .as_ref()
+                        .map(|error| { text(error).size(14).style(text::danger) }),
+                ]
+                .spacing(5)
+                .into()
+            }
+            _ => row![
+                self.severity_text(self.displayed_length(path).to_string(), path)
+                    .width(Length::Fixed(80.0)),
+                self.baseline
+                    .as_ref()
+                    .is_some_and(|baseline| !baseline.contains(&path.path))
+                    .then(|| text("New").style(text::warning)),
+                path.archive
+                    .is_some()
+                    .then(|| text("In archive").style(text::secondary)),
+                self.severity_text(self.displayed_path(path), path)
+                    .wrapping(text::Wrapping::None)
+                    .width(Length::Fill),
+                self.suggest_paths.then(|| {
+                    text(
+                        suggest_path(Path::new(&path.path), self.scan_limit, self.scan_metric)
+                            .unwrap_or_else(|| "No suggestion".to_string()),
+                    )
+                    .wrapping(text::Wrapping::None)
+                    .width(Length::Fill)
+                }),
+                button(text(if self.copied.as_ref() == Some(&path.path) {
+                    "Copied!"
+                } else {
+                    "Copy"
+                }))
+                .on_press(Message::CopyPath(path.path.clone())),
+                // Entries of archives don't exist yet, the archive does
+                button(text("Open Folder")).on_press(Message::RevealPath(
+                    path.archive.as_deref().unwrap_or(&path.path).to_string()
+                )),
+                button(text("Rename")).on_press_maybe(
+                    (self.can_rename() && path.archive.is_none())
+                        .then(|| Message::StartRename(path.path.clone()))
+                ),
+                checkbox("", self.selected.contains(&path.path))
+                    .on_toggle(|selected| Message::SelectionToggled(path.path.clone(), selected)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center)
+            .into(),
+        }
+    }
+
+    fn fixed_paths_view(&self) -> Option<iced::Element<'_, Message>> {
+        use iced::widget::{column, *};
+
+        if self.fixed_paths.is_empty() {
+            return None;
+        }
+
+        let fixed = self.fixed_paths.iter().enumerate().map(|(index, fixed)| {
+            let change = match fixed.after {
+                Some(after) => format!("{} -> {}: {}", fixed.before, after, fixed.path),
+                None => format!("{} -> resolved: {}", fixed.before, fixed.path),
+            };
+            row![
+                text(change).width(Length::Fill),
+                fixed
+                    .undo
+                    .is_some()
+                    .then(|| button(text("Undo")).on_press(Message::UndoFix(index))),
+            ]
+            .spacing(10)
+            .into()
+        });
+
+        Some(
+            column![
+                text(format!("Fixed this session ({})", self.fixed_paths.len())).size(18),
+                scrollable(column(fixed))
+                    .height(Length::Fixed(150.0))
+                    .width(Length::Fill)
+            ]
+            .into(),
+        )
+    }
+
+    /// Colors a part of a result by how far it's over the limit, if an error limit is set.
+    fn severity_text<'a>(
+        &self,
+        content: impl iced::widget::text::IntoFragment<'a>,
+        path: &OverLimit,
+    ) -> iced::widget::Text<'a> {
+        use iced::widget::text;
+
+        let content = text(content);
+        match path.severity(self.error_limit) {
+            Some(Severity::Warning) => content.style(text::warning),
+            Some(Severity::Error) => content.style(text::danger),
+            None => content,
+        }
+    }
+
+    /// The path as it's shown and exported, see [`UI::relative_paths`] and [`UI::separators`].
+    fn displayed_path<'a>(&self, path: &'a OverLimit) -> Cow<'a, str> {
+        let shown = if self.relative_paths {
+            path.relative_path()
+        } else {
+            Cow::Borrowed(path.path.as_str())
+        };
+        self.separators.apply(shown)
+    }
+
+    /// The length of [`UI::displayed_path`].
+    fn displayed_length(&self, path: &OverLimit) -> u64 {
+        if self.relative_paths {
+            self.measure_path(Path::new(&*path.relative_path()))
+        } else {
+            path.size
+        }
+    }
+
+    /// Measures `path` the way the last scan did, see [`ScanOptions::measure`].
+    fn measure_path(&self, path: &Path) -> u64 {
+        match &self.scan_options {
+            Some(options) => options.measure(path) as u64,
+            None => self.scan_metric.measure(path) as u64,
+        }
+    }
+
+    /// Takes the settings a scan of `roots` with `options` runs with, apart from the options.
+    fn scan_setup(&self, roots: &[PathBuf], options: &ScanOptions) -> ScanSetup {
+        ScanSetup {
+            max_duration: self.max_duration,
+            count_first: self.count_first,
+            deduplicate: self.deduplicate,
+            live_csv: self.live_csv.clone().map(|path| {
+                let options = export::CsvOptions {
+                    limit: options.limit,
+                    metric: options.metric,
+                    suggested_names: self.export_suggested_names,
+                    name_lengths: self.export_name_lengths,
+                    roots: roots.len() > 1,
+                    error_limit: self.error_limit,
+                    suggested_paths: self.suggest_paths,
+                    relative: self.relative_paths,
+                    delimiter: self.delimiter,
+                    separators: self.separators,
+                };
+                (path, options)
+            }),
+        }
+    }
+
+    /// Starts scanning `roots` with `options` and `setup`, and remembers them for a rescan.
+    ///
+    /// The results of the previous scan have to be cleared first.
+    fn run_scan(
         &mut self,
-        root: PathBuf,
-        limit: usize,
+        roots: Vec<PathBuf>,
+        options: ScanOptions,
+        setup: ScanSetup,
+    ) -> Task<Message> {
+        self.scan_started = Some(Instant::now());
+        self.what_if_limit = options.limit.min(LengthHistogram::MAX_LENGTH) as u32;
+        let token = CancellationToken::new();
+        self.scan_status = ScanStatus::Scanning(token.clone());
+        let (paused_sender, paused) = watch::channel(false);
+        self.scan_paused = Some(paused_sender);
+        self.scan_roots = roots.clone();
+        self.scan_limit = options.limit;
+        self.scan_metric = options.metric;
+        self.scan_extensions = options.extensions.clone();
+        self.scan_options = Some(options.clone());
+        let count_first = setup.count_first;
+        let max_duration = setup.max_duration;
+        self.scan_setup = Some(setup);
+        self.time_limited = false;
+
+        // Races the scan, which cancels the token once it completes on its own
+        let time_limit = match max_duration {
+            Some(max_duration) => {
+                let token = token.clone();
+                Task::future(async move {
+                    token
+                        .run_until_cancelled(tokio::time::sleep(max_duration))
+                        .await
+                })
+                .and_then(|()| Task::done(Message::TimeLimitReached))
+            }
+            None => Task::none(),
+        };
+
+        // Counting first measures once the count is in, see `Message::EntriesCounted`
+        let scan = if count_first {
+            self.phase = Some(Phase::Counting);
+            Task::future(async move {
+                token
+                    .run_until_cancelled(scanner::count_entries(roots, &options))
+                    .await
+            })
+            .map(Message::EntriesCounted)
+        } else {
+            Task::batch([
+                start_precount(roots.clone(), options.clone(), token.clone()),
+                self.start_scan(roots, options, token, paused),
+            ])
+        };
+
+        Task::batch([query_disk_space(self.scan_roots.clone()), time_limit, scan])
+    }
+
+    fn start_scan(
+        &self,
+        roots: Vec<PathBuf>,
+        options: ScanOptions,
         token: CancellationToken,
+        paused: watch::Receiver<bool>,
     ) -> Task<Message> {
-        let sipper = sipper(move |mut sender| async move {
-            let mut stack = vec![root];
-
-            let mut scanned: u64 = 0;
-            let mut over_limit: Vec<OverLimit> = Vec::new();
-            let mut last_update = Instant::now();
-
-            token
-                .run_until_cancelled(async move {
-                    while let Some(path) = stack.pop() {
-                        match fs::read_dir(&path).await {
-                            Ok(mut entries) => {
-                                while let Ok(Some(entry)) = entries.next_entry().await {
-                                    let entry_path = entry.path();
-                                    let path_length = entry_path.as_os_str().len();
-
-                                    if path_length > limit {
-                                        over_limit.push(OverLimit {
-                                            path: entry_path
-                                                .as_os_str()
-                                                .to_string_lossy()
-                                                .to_string(),
-                                            size: path_length as u64,
-                                        });
-                                    }
+        let live_csv = self
+            .scan_setup
+            .as_ref()
+            .and_then(|setup| setup.live_csv.clone());
 
-                                    match entry.metadata().await {
-                                        Ok(metadata) => {
-                                            if metadata.is_dir() {
-                                                stack.push(entry_path);
-                                            }
-                                        }
-                                        Err(err) => {
-                                            sender
-                                                .send(Message::Error(format!(
-                                                    "Error reading metadata for {}: {}",
-                                                    entry_path.display(),
-                                                    err
-                                                )))
-                                                .await;
-                                        }
-                                    }
+        let events = Box::pin(scanner::scan_stream(roots, options, token, Some(paused)));
 
-                                    scanned += 1;
-
-                                    let now = Instant::now();
-                                    if now - last_update > Duration::from_millis(100) {
-                                        sender
-                                            .send(Message::ScanUpdate {
-                                                now_scanned: scanned,
-                                                new_paths_over_limit: mem::take(&mut over_limit),
-                                            })
-                                            .await;
-                                        last_update = now;
-                                    }
-                                }
-                            }
-                            Err(err) => {
-                                sender
-                                    .send(Message::Error(format!(
-                                        "Error reading directory {}: {}",
-                                        path.display(),
-                                        err
-                                    )))
-                                    .await;
-                            }
+        // Each event turns into its message, plus any error writing the live CSV file
+        let messages = stream::unfold(
+            (events, live_csv, None::<CsvWriter>),
+            |(mut events, mut live_csv, mut writer)| async move {
+                let mut messages = Vec::new();
+                if let Some((path, options)) = live_csv.take() {
+                    match CsvWriter::create(&path, options).await {
+                        Ok(created) => writer = Some(created),
+                        Err(err) => messages.push(Message::Error(err)),
+                    }
+                }
+
+                // A scan always yields at least its final progress, so nothing is lost here
+                let event = events.next().await?;
+                let message = match event {
+                    ScanEvent::Progress(progress) => {
+                        if let Some(open) = &mut writer
+                            && let Err(err) = open.write_rows(&progress.over_limit).await
+                        {
+                            // The rows written so far stay, but the file won't be complete
+                            writer = None;
+                            messages.push(Message::Error(err));
                         }
+                        Message::ScanUpdate(progress)
                     }
+                    ScanEvent::Error(error) => Message::ScanError(error),
+                };
+                messages.push(message);
 
-                    sender
-                        .send(Message::ScanUpdate {
-                            now_scanned: scanned,
-                            new_paths_over_limit: mem::take(&mut over_limit),
-                        })
-                        .await;
+                Some((stream::iter(messages), (events, live_csv, writer)))
+            },
+        )
+        .flatten();
+
+        Task::run(messages, |message| message).chain(Task::done(Message::ScanComplete))
+    }
+}
+
+/// Over-limit results sharing the same parent directory or drive.
+struct ResultGroup<'a> {
+    name: String,
+    paths: Vec<&'a OverLimit>,
+    longest: &'a OverLimit,
+}
+
+/// Queries the space of the drives `roots` are on, leaving out those it can't be determined for.
+fn query_disk_space(roots: Vec<PathBuf>) -> Task<Message> {
+    Task::future(async move {
+        // A network drive may take a moment to answer, so this doesn't block the runtime
+        tokio::task::spawn_blocking(move || {
+            roots
+                .into_iter()
+                .filter_map(|root| DiskSpace::of(&root).ok().map(|space| (root, space)))
+                .collect()
+        })
+        .await
+        .unwrap_or_default()
+    })
+    .map(Message::DiskSpaceQueried)
+}
+
+/// Runs `write` unless the export is aborted first, which removes the partial file.
+///
+/// Like an aborted scan, the export stops at the next point it waits for the disk.
+async fn abortable_write(
+    token: CancellationToken,
+    file_path: &Path,
+    write: impl Future<Output = Result<(), String>>,
+) -> Result<(), String> {
+    match token.run_until_cancelled(write).await {
+        Some(result) => result,
+        None => {
+            let _ = fs::remove_file(file_path).await;
+            Err("Export aborted, the partial file was removed".to_string())
+        }
+    }
+}
+
+/// Notes in the success message of an export that it replaced an existing file.
+///
+/// Not every save dialog asks before picking an existing file, so this makes sure nothing gets
+/// overwritten without the user hearing about it. Has to be checked before writing.
+async fn replaced_note(file_path: &Path) -> &'static str {
+    if fs::try_exists(file_path).await.unwrap_or(false) {
+        ", replacing the existing file"
+    } else {
+        ""
+    }
+}
+
+/// Parses a limit typed in by the user.
+///
+/// Anything longer than the longest path Windows supports is rejected as a likely typo.
+fn parse_limit(input: &str) -> Result<usize, String> {
+    let limit = input
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| "The limit must be a whole number".to_string())?;
+    if limit == 0 || limit > LengthHistogram::MAX_LENGTH {
+        return Err(format!(
+            "The limit must be between 1 and {}",
+            LengthHistogram::MAX_LENGTH
+        ));
+    }
+    Ok(limit)
+}
+
+/// Splits a comma separated list of glob patterns typed in by the user.
+fn split_patterns(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compiles the patterns of the `kind` option, logging the invalid ones instead.
+fn compile_patterns(patterns: &[String], kind: &str, errors: &mut ErrorLog) -> Vec<glob::Pattern> {
+    patterns
+        .iter()
+        .filter_map(|pattern| {
+            glob::Pattern::new(pattern)
+                .inspect_err(|err| {
+                    errors.push_message(
+                        io::ErrorKind::InvalidInput,
+                        format!("Invalid {} pattern {}: {}", kind, pattern, err),
+                    )
                 })
-                .await;
-        });
+                .ok()
+        })
+        .collect()
+}
 
-        Task::sip(sipper, |value| value, |_| Message::ScanComplete)
+fn load_project_limit(root: PathBuf) -> Task<Message> {
+    Task::future(async move {
+        let limit = project::read_limit(&root).await;
+        Message::ProjectLimitLoaded { root, limit }
+    })
+}
+
+/// Runs [`scanner::count_entries`] until `token` is cancelled, to show the progress of the scan.
+fn start_precount(
+    roots: Vec<PathBuf>,
+    options: ScanOptions,
+    token: CancellationToken,
+) -> Task<Message> {
+    Task::future(async move {
+        token
+            .run_until_cancelled(scanner::count_entries(roots, &options))
+            .await
+    })
+    .and_then(|count| Task::done(Message::PreCountComplete(count.total())))
+}
+
+/// Renames `old_path` to `new_name` within the same directory.
+///
+/// Fails if the new path, measured like the scan did, would still be over the limit or if it
+/// already exists. Returns the old and the new path on success.
+async fn rename_path(
+    old_path: PathBuf,
+    new_name: String,
+    options: &ScanOptions,
+) -> Result<(String, String), String> {
+    if new_name.is_empty() || new_name.contains(['/', '\\']) {
+        return Err("The new name must not be empty or contain path separators".to_string());
+    }
+
+    let parent = old_path
+        .parent()
+        .ok_or_else(|| format!("{} has no parent directory", old_path.display()))?;
+    let new_path = parent.join(&new_name);
+
+    let new_length = options.measure(&new_path);
+    if new_length > options.limit {
+        return Err(format!(
+            "The new path is {} long, which is still over the limit of {}",
+            new_length, options.limit
+        ));
+    }
+
+    match fs::symlink_metadata(&new_path).await {
+        Ok(_) => return Err(format!("{} already exists", new_path.display())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => {
+            return Err(format!(
+                "Failed to check whether {} exists: {}",
+                new_path.display(),
+                err
+            ));
+        }
+    }
+
+    fs::rename(&old_path, &new_path)
+        .await
+        .map_err(|err| format!("Failed to rename {}: {}", old_path.display(), err))?;
+
+    Ok((
+        old_path.to_string_lossy().to_string(),
+        new_path.to_string_lossy().to_string(),
+    ))
+}
+
+/// Renames `new_path` back to `old_path`, returning both like [`rename_path`] did.
+///
+/// The old path is usually over the limit, that's why it was renamed, so it isn't checked.
+async fn undo_rename(old_path: String, new_path: String) -> Result<(String, String), String> {
+    match fs::symlink_metadata(&old_path).await {
+        Ok(_) => return Err(format!("{} already exists again", old_path)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => {
+            return Err(format!(
+                "Failed to check whether {} exists: {}",
+                old_path, err
+            ));
+        }
+    }
+
+    fs::rename(&new_path, &old_path)
+        .await
+        .map_err(|err| format!("Failed to rename {} back: {}", new_path, err))?;
+
+    Ok((old_path, new_path))
+}
+
+/// Formats a count with thousands separators, e.g. `12,345`.
+fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Formats a duration to the second, e.g. `2m 15s`.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}h {}m", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
     }
 }
 
@@ -476,6 +4007,21 @@ pub enum Link {
 }
 
 const FONT_SIZE: f32 = 14.0;
+
+/// The height of a single row in the results list.
+const RESULT_ROW_HEIGHT: f32 = 36.0;
+/// The most results shown for a single folder while grouping by folder.
+const MAX_GROUP_ROWS: usize = 500;
+/// The most paths the top list can be set to show.
+const MAX_TOP_PATHS: usize = 1_000;
+/// How finely the over limit ratio bar is divided.
+const RATIO_PORTIONS: u16 = 1_000;
+/// How many of the longest paths the summary keeps.
+const SUMMARY_LONGEST: usize = 5;
+/// How long typing in the limit has to pause before the new limit is applied.
+const LIMIT_DEBOUNCE: Duration = Duration::from_millis(300);
+/// How often the status refreshes while scanning, even without new progress.
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
 fn footer<'a>() -> iced::Element<'a, Message> {
     use iced::widget::*;
     let text = |content| text(content).font(Font::MONOSPACE).size(FONT_SIZE);